@@ -0,0 +1,255 @@
+use info;
+use std::cmp;
+
+/// A color that text can be drawn with.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub enum Color {
+    /// The terminal's own default color.
+    DefaultColor,
+    /// One of the eight basic colors.
+    AnsiColor(info::Color),
+    /// The bright version of one of the eight basic colors.
+    BrightColor(info::Color),
+    /// An entry in the 256 color palette, where the first 16 are the basic
+    /// and bright colors.
+    IndexedColor(u8),
+    /// A 24-bit color, as red, green and blue.
+    RgbColor(u8, u8, u8),
+}
+
+impl Color {
+    /// The palette index of this color, if it has one.
+    pub fn index (&self) -> Option<uint> {
+        match *self {
+            DefaultColor    => None,
+            AnsiColor(c)    => Some(c as uint),
+            BrightColor(c)  => Some(c as uint + 8),
+            IndexedColor(n) => Some(n as uint),
+            RgbColor(..)    => None,
+        }
+    }
+
+    /// The red, green and blue values of this color, if it has them.
+    pub fn to_rgb (&self) -> Option<(u8, u8, u8)> {
+        match *self {
+            DefaultColor      => None,
+            RgbColor(r, g, b) => Some((r, g, b)),
+            _                 => Some(rgb(self.index().unwrap() as u8)),
+        }
+    }
+}
+
+/// How colors the terminal doesn't have are replaced.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub enum Mapping {
+    /// Use whichever available color looks closest.
+    PerceptualMapping,
+    /**
+     * Use the same mapping as xterm does for 24-bit colors, which picks from
+     * the 6x6x6 color cube and the grayscale ramp of the 256 color palette.
+     * Terminals with fewer colors still use the closest basic color.
+     */
+    XtermMapping,
+}
+
+/// The colors a terminal can show.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub struct Palette {
+    /// The number of palette entries, or 0 if colors can't be used at all.
+    pub colors: uint,
+    /// Whether 24-bit colors can be used.
+    pub truecolor: bool,
+    pub mapping: Mapping,
+}
+
+impl Palette {
+    pub fn new (colors: uint, truecolor: bool) -> Palette {
+        Palette {
+            colors: colors,
+            truecolor: truecolor && colors > 0,
+            mapping: PerceptualMapping,
+        }
+    }
+
+    /**
+     * The closest color to `color` which is in this palette.
+     *
+     * Every color becomes `DefaultColor` if there aren't any colors.
+     */
+    pub fn approximate (&self, color: Color) -> Color {
+        if self.colors == 0 {
+            return DefaultColor;
+        }
+        let (r, g, b) = match color {
+            DefaultColor      => return color,
+            RgbColor(r, g, b) => {
+                if self.truecolor {
+                    return color;
+                }
+                (r, g, b)
+            }
+            // the basic colors are usually the same as the bright ones,
+            // only drawn bold, so stay with the same hue (if the terminal
+            // has all of them)
+            BrightColor(c) if self.colors >= 8 && self.colors < 16 => {
+                return AnsiColor(c)
+            }
+            _                 => {
+                let n = color.index().unwrap();
+                if n < self.colors {
+                    return color;
+                }
+                let (r, g, b) = rgb(n as u8);
+                if self.truecolor {
+                    return RgbColor(r, g, b);
+                }
+                (r, g, b)
+            }
+        };
+
+        if self.colors >= 256 && self.mapping == XtermMapping {
+            return from_index(xterm_index(r, g, b));
+        }
+        let n = cmp::min(self.colors, 256);
+        let nearest = range(0, n).min_by(|&i| {
+            distance((r, g, b), rgb(i as u8))
+        });
+        from_index(nearest.unwrap() as u8)
+    }
+}
+
+/// The basic color with palette index `n`, which must be less than 8.
+pub fn ansi (n: uint) -> info::Color {
+    match n {
+        0 => info::ColorBlack,
+        1 => info::ColorRed,
+        2 => info::ColorGreen,
+        3 => info::ColorYellow,
+        4 => info::ColorBlue,
+        5 => info::ColorMagenta,
+        6 => info::ColorCyan,
+        7 => info::ColorWhite,
+        _ => fail!("{} isn't a basic color", n),
+    }
+}
+
+/**
+ * The color with palette index `n`, using the most specific variant.
+ *
+ * Indexes up to 7 give `AnsiColor`, up to 15 give `BrightColor`, and the
+ * rest give `IndexedColor`.
+ */
+pub fn from_index (n: u8) -> Color {
+    match n {
+        0..7  => AnsiColor(ansi(n as uint)),
+        8..15 => BrightColor(ansi(n as uint - 8)),
+        _     => IndexedColor(n),
+    }
+}
+
+/// The red, green and blue values of palette entry `n`, as xterm has them.
+pub fn rgb (n: u8) -> (u8, u8, u8) {
+    match n {
+        0..15    => Basic[n as uint],
+        16..231  => {
+            let n = n as uint - 16;
+            (Levels[n / 36], Levels[n / 6 % 6], Levels[n % 6])
+        }
+        _        => {
+            let v = 8 + 10 * (n - 232);
+            (v, v, v)
+        }
+    }
+}
+
+static Basic: [(u8, u8, u8), ..16] = [
+    (0x00, 0x00, 0x00), (0xcd, 0x00, 0x00), (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00), (0x00, 0x00, 0xee), (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd), (0xe5, 0xe5, 0xe5), (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00), (0x00, 0xff, 0x00), (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff), (0xff, 0x00, 0xff), (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+// the values each channel can have in the color cube
+static Levels: [u8, ..6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+// how different two colors look. this is the "redmean" approximation, which
+// weighs the channels depending on how red the colors are, and is a lot
+// cheaper than converting to a proper color space.
+fn distance (a: (u8, u8, u8), b: (u8, u8, u8)) -> uint {
+    let (r1, g1, b1) = a;
+    let (r2, g2, b2) = b;
+    let rmean = (r1 as int + r2 as int) / 2;
+    let dr = r1 as int - r2 as int;
+    let dg = g1 as int - g2 as int;
+    let db = b1 as int - b2 as int;
+    (((512 + rmean) * dr * dr >> 8) + 4 * dg * dg
+        + ((767 - rmean) * db * db >> 8)) as uint
+}
+
+// the nearest entry in the color cube or the grayscale ramp, the way xterm
+// itself picks them
+fn xterm_index (r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| -> uint {
+        if v < 48 { 0 } else if v < 115 { 1 } else { (v as uint - 35) / 40 }
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (Levels[ri], Levels[gi], Levels[bi]);
+
+    let avg = (r as uint + g as uint + b as uint) / 3;
+    let gray = if avg > 238 { 23 } else if avg < 8 { 0 } else { (avg - 3) / 10 };
+    let v = (8 + 10 * gray) as u8;
+
+    if distance((r, g, b), (v, v, v)) < distance((r, g, b), cube) {
+        (232 + gray) as u8
+    }
+    else {
+        (16 + 36 * ri + 6 * gi + bi) as u8
+    }
+}
+
+#[test]
+fn test_index () {
+    assert!(DefaultColor.index() == None);
+    assert!(AnsiColor(info::ColorBlue).index() == Some(4));
+    assert!(BrightColor(info::ColorBlue).index() == Some(12));
+    assert!(IndexedColor(200).index() == Some(200));
+    assert!(RgbColor(1, 2, 3).index() == None);
+
+    for n in range(0u, 256) {
+        assert!(from_index(n as u8).index() == Some(n));
+    }
+    assert!(from_index(9) == BrightColor(info::ColorRed));
+}
+
+#[test]
+fn test_approximate () {
+    let none = Palette::new(0, true);
+    assert!(none.approximate(AnsiColor(info::ColorRed)) == DefaultColor);
+    assert!(none.approximate(RgbColor(1, 2, 3)) == DefaultColor);
+
+    let basic = Palette::new(8, false);
+    assert!(basic.approximate(BrightColor(info::ColorBlue))
+            == AnsiColor(info::ColorBlue));
+    assert!(basic.approximate(RgbColor(250, 10, 20))
+            == AnsiColor(info::ColorRed));
+    assert!(basic.approximate(IndexedColor(28)) == AnsiColor(info::ColorGreen));
+    assert!(basic.approximate(DefaultColor) == DefaultColor);
+
+    // blue isn't one of the first four colors
+    let few = Palette::new(4, false);
+    let n = few.approximate(BrightColor(info::ColorBlue)).index().unwrap();
+    assert!(n < 4);
+
+    let mut xterm = Palette::new(256, false);
+    assert!(xterm.approximate(IndexedColor(100)) == IndexedColor(100));
+    assert!(xterm.approximate(RgbColor(0xaf, 0x5f, 0xd7)) == IndexedColor(134));
+    xterm.mapping = XtermMapping;
+    assert!(xterm.approximate(RgbColor(0xb0, 0x60, 0xd0)) == IndexedColor(134));
+    assert!(xterm.approximate(RgbColor(0x80, 0x80, 0x82)) == IndexedColor(244));
+
+    let direct = Palette::new(16, true);
+    assert!(direct.approximate(IndexedColor(231)) == RgbColor(255, 255, 255));
+    assert!(direct.approximate(RgbColor(1, 2, 3)) == RgbColor(1, 2, 3));
+}
@@ -0,0 +1,569 @@
+// compiled terminfo entries for the tests, so that they don't depend on the
+// terminfo database of the machine running them. each one is a real entry cut
+// down to the capabilities that TermWriter and the tests look at.
+
+// compiled with tic -x from:
+//   xterm-256color|xterm with 256 colors, for tests,
+//       am, bce, msgr, xenl,
+//       colors#256,
+//       blink=\E[5m, bold=\E[1m, civis=\E[?25l, clear=\E[H\E[2J,
+//       cnorm=\E[?12l\E[?25h, cr=\r, csr=\E[%i%p1%d;%p2%dr, cub=\E[%p1%dD,
+//       cub1=^H, cud=\E[%p1%dB, cud1=\n, cuf=\E[%p1%dC, cuf1=\E[C,
+//       cup=\E[%i%p1%d;%p2%dH, cuu=\E[%p1%dA, cuu1=\E[A, dch=\E[%p1%dP,
+//       dch1=\E[P, dim=\E[2m, dl=\E[%p1%dM, dl1=\E[M, ech=\E[%p1%dX,
+//       ed=\E[J, home=\E[H, hpa=\E[%i%p1%dG, ich=\E[%p1%d@, il=\E[%p1%dL,
+//       il1=\E[L, ind=\n, indn=\E[%p1%dS, invis=\E[8m, kcub1=\EOD,
+//       kcud1=\EOB, kcuf1=\EOC, kcuu1=\EOA, nel=\EE, op=\E[39;49m, rc=\E8,
+//       rev=\E[7m, ri=\EM, rin=\E[%p1%dT, ritm=\E[23m,
+//       rmcup=\E[?1049l\E[23;0;0t, rmir=\E[4l, rmkx=\E[?1l\E>,
+//       rmso=\E[27m, rmul=\E[24m, sc=\E7,
+//       setab=\E[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m,
+//       setaf=\E[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m,
+//       sgr=%?%p9%t\E(0%e\E(B%;\E[0%?%p6%t;1%;%?%p5%t;2%;%?%p2%t;4%;%?%p1%p3%|%t;7%;%?%p4%t;5%;%?%p7%t;8%;m,
+//       sgr0=\E(B\E[m, sitm=\E[3m, smcup=\E[?1049h\E[22;0;0t, smir=\E[4h,
+//       smkx=\E[?1h\E=, smso=\E[7m, smul=\E[4m, vpa=\E[%i%p1%dd,
+//       rmxx=\E[29m, smxx=\E[9m,
+pub static XtermEntry: [u8, ..1483] = [
+    0x1a, 0x01, 0x30, 0x00, 0x1d, 0x00, 0x0e, 0x00, 0x69, 0x01, 0x5b, 0x02,
+    0x78, 0x74, 0x65, 0x72, 0x6d, 0x2d, 0x32, 0x35, 0x36, 0x63, 0x6f, 0x6c,
+    0x6f, 0x72, 0x7c, 0x78, 0x74, 0x65, 0x72, 0x6d, 0x20, 0x77, 0x69, 0x74,
+    0x68, 0x20, 0x32, 0x35, 0x36, 0x20, 0x63, 0x6f, 0x6c, 0x6f, 0x72, 0x73,
+    0x2c, 0x20, 0x66, 0x6f, 0x72, 0x20, 0x74, 0x65, 0x73, 0x74, 0x73, 0x00,
+    0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x01, 0xff, 0xff,
+    0xff, 0xff, 0x00, 0x00, 0x02, 0x00, 0xff, 0xff, 0x13, 0x00, 0xff, 0xff,
+    0x1b, 0x00, 0x1f, 0x00, 0xff, 0xff, 0x2a, 0x00, 0x3b, 0x00, 0x3d, 0x00,
+    0x41, 0x00, 0x48, 0x00, 0xff, 0xff, 0x4a, 0x00, 0x57, 0x00, 0xff, 0xff,
+    0x5b, 0x00, 0xff, 0xff, 0x5f, 0x00, 0x63, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x67, 0x00, 0x6c, 0x00, 0x71, 0x00, 0xff, 0xff, 0x83, 0x00,
+    0x88, 0x00, 0x8d, 0x00, 0xff, 0xff, 0x92, 0x00, 0x97, 0x00, 0x9c, 0x00,
+    0xa1, 0x00, 0xff, 0xff, 0xaa, 0x00, 0xb1, 0x00, 0xff, 0xff, 0xc3, 0x00,
+    0xc8, 0x00, 0xce, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xd4, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xd8, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xdc, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xe0, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xe4, 0x00, 0xe8, 0x00, 0xf0, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xf8, 0x00, 0xff, 0xff, 0xfb, 0x00, 0x04, 0x01, 0x0d, 0x01, 0x16, 0x01,
+    0x1f, 0x01, 0x28, 0x01, 0x31, 0x01, 0x3a, 0x01, 0x43, 0x01, 0x4c, 0x01,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x55, 0x01,
+    0x58, 0x01, 0x63, 0x01, 0x66, 0x01, 0x68, 0x01, 0x6b, 0x01, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xc8, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xd1, 0x01, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xd6, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xdc, 0x01, 0x1b, 0x02,
+    0x0d, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b,
+    0x25, 0x70, 0x32, 0x25, 0x64, 0x72, 0x00, 0x1b, 0x5b, 0x48, 0x1b, 0x5b,
+    0x32, 0x4a, 0x00, 0x1b, 0x5b, 0x4a, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25,
+    0x70, 0x31, 0x25, 0x64, 0x47, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70,
+    0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32, 0x25, 0x64, 0x48, 0x00, 0x0a,
+    0x00, 0x1b, 0x5b, 0x48, 0x00, 0x1b, 0x5b, 0x3f, 0x32, 0x35, 0x6c, 0x00,
+    0x08, 0x00, 0x1b, 0x5b, 0x3f, 0x31, 0x32, 0x6c, 0x1b, 0x5b, 0x3f, 0x32,
+    0x35, 0x68, 0x00, 0x1b, 0x5b, 0x43, 0x00, 0x1b, 0x5b, 0x41, 0x00, 0x1b,
+    0x5b, 0x50, 0x00, 0x1b, 0x5b, 0x4d, 0x00, 0x1b, 0x5b, 0x35, 0x6d, 0x00,
+    0x1b, 0x5b, 0x31, 0x6d, 0x00, 0x1b, 0x5b, 0x3f, 0x31, 0x30, 0x34, 0x39,
+    0x68, 0x1b, 0x5b, 0x32, 0x32, 0x3b, 0x30, 0x3b, 0x30, 0x74, 0x00, 0x1b,
+    0x5b, 0x32, 0x6d, 0x00, 0x1b, 0x5b, 0x34, 0x68, 0x00, 0x1b, 0x5b, 0x38,
+    0x6d, 0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x00,
+    0x1b, 0x5b, 0x34, 0x6d, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64,
+    0x58, 0x00, 0x1b, 0x28, 0x42, 0x1b, 0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x3f,
+    0x31, 0x30, 0x34, 0x39, 0x6c, 0x1b, 0x5b, 0x32, 0x33, 0x3b, 0x30, 0x3b,
+    0x30, 0x74, 0x00, 0x1b, 0x5b, 0x34, 0x6c, 0x00, 0x1b, 0x5b, 0x32, 0x37,
+    0x6d, 0x00, 0x1b, 0x5b, 0x32, 0x34, 0x6d, 0x00, 0x1b, 0x5b, 0x4c, 0x00,
+    0x1b, 0x4f, 0x42, 0x00, 0x1b, 0x4f, 0x44, 0x00, 0x1b, 0x4f, 0x43, 0x00,
+    0x1b, 0x4f, 0x41, 0x00, 0x1b, 0x5b, 0x3f, 0x31, 0x6c, 0x1b, 0x3e, 0x00,
+    0x1b, 0x5b, 0x3f, 0x31, 0x68, 0x1b, 0x3d, 0x00, 0x1b, 0x45, 0x00, 0x1b,
+    0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x50, 0x00, 0x1b, 0x5b, 0x25, 0x70,
+    0x31, 0x25, 0x64, 0x4d, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64,
+    0x42, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x40, 0x00, 0x1b,
+    0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x53, 0x00, 0x1b, 0x5b, 0x25, 0x70,
+    0x31, 0x25, 0x64, 0x4c, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64,
+    0x44, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x43, 0x00, 0x1b,
+    0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x54, 0x00, 0x1b, 0x5b, 0x25, 0x70,
+    0x31, 0x25, 0x64, 0x41, 0x00, 0x1b, 0x38, 0x00, 0x1b, 0x5b, 0x25, 0x69,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x64, 0x00, 0x1b, 0x37, 0x00, 0x0a, 0x00,
+    0x1b, 0x4d, 0x00, 0x25, 0x3f, 0x25, 0x70, 0x39, 0x25, 0x74, 0x1b, 0x28,
+    0x30, 0x25, 0x65, 0x1b, 0x28, 0x42, 0x25, 0x3b, 0x1b, 0x5b, 0x30, 0x25,
+    0x3f, 0x25, 0x70, 0x36, 0x25, 0x74, 0x3b, 0x31, 0x25, 0x3b, 0x25, 0x3f,
+    0x25, 0x70, 0x35, 0x25, 0x74, 0x3b, 0x32, 0x25, 0x3b, 0x25, 0x3f, 0x25,
+    0x70, 0x32, 0x25, 0x74, 0x3b, 0x34, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70,
+    0x31, 0x25, 0x70, 0x33, 0x25, 0x7c, 0x25, 0x74, 0x3b, 0x37, 0x25, 0x3b,
+    0x25, 0x3f, 0x25, 0x70, 0x34, 0x25, 0x74, 0x3b, 0x35, 0x25, 0x3b, 0x25,
+    0x3f, 0x25, 0x70, 0x37, 0x25, 0x74, 0x3b, 0x38, 0x25, 0x3b, 0x6d, 0x00,
+    0x1b, 0x5b, 0x33, 0x39, 0x3b, 0x34, 0x39, 0x6d, 0x00, 0x1b, 0x5b, 0x33,
+    0x6d, 0x00, 0x1b, 0x5b, 0x32, 0x33, 0x6d, 0x00, 0x1b, 0x5b, 0x25, 0x3f,
+    0x25, 0x70, 0x31, 0x25, 0x7b, 0x38, 0x7d, 0x25, 0x3c, 0x25, 0x74, 0x33,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x25, 0x65, 0x25, 0x70, 0x31, 0x25, 0x7b,
+    0x31, 0x36, 0x7d, 0x25, 0x3c, 0x25, 0x74, 0x39, 0x25, 0x70, 0x31, 0x25,
+    0x7b, 0x38, 0x7d, 0x25, 0x2d, 0x25, 0x64, 0x25, 0x65, 0x33, 0x38, 0x3b,
+    0x35, 0x3b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x25, 0x3b, 0x6d, 0x00, 0x1b,
+    0x5b, 0x25, 0x3f, 0x25, 0x70, 0x31, 0x25, 0x7b, 0x38, 0x7d, 0x25, 0x3c,
+    0x25, 0x74, 0x34, 0x25, 0x70, 0x31, 0x25, 0x64, 0x25, 0x65, 0x25, 0x70,
+    0x31, 0x25, 0x7b, 0x31, 0x36, 0x7d, 0x25, 0x3c, 0x25, 0x74, 0x31, 0x30,
+    0x25, 0x70, 0x31, 0x25, 0x7b, 0x38, 0x7d, 0x25, 0x2d, 0x25, 0x64, 0x25,
+    0x65, 0x34, 0x38, 0x3b, 0x35, 0x3b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x25,
+    0x3b, 0x6d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x04, 0x00,
+    0x15, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x05, 0x00, 0x1b, 0x5b,
+    0x32, 0x39, 0x6d, 0x00, 0x1b, 0x5b, 0x39, 0x6d, 0x00, 0x72, 0x6d, 0x78,
+    0x78, 0x00, 0x73, 0x6d, 0x78, 0x78, 0x00,
+];
+
+// compiled with tic from:
+//   linux|linux console, for tests,
+//       am, bce, msgr, xenl,
+//       colors#8, ncv#18,
+//       blink=\E[5m, bold=\E[1m, civis=\E[?25l\E[?1c, clear=\E[H\E[J,
+//       cnorm=\E[?25h\E[?0c, cr=\r, csr=\E[%i%p1%d;%p2%dr, cub=\E[%p1%dD,
+//       cub1=^H, cud=\E[%p1%dB, cud1=\n, cuf=\E[%p1%dC, cuf1=\E[C,
+//       cup=\E[%i%p1%d;%p2%dH, cuu=\E[%p1%dA, cuu1=\E[A, dch=\E[%p1%dP,
+//       dch1=\E[P, dim=\E[2m, dl=\E[%p1%dM, dl1=\E[M, ech=\E[%p1%dX,
+//       ed=\E[J, home=\E[H, hpa=\E[%i%p1%dG, ich=\E[%p1%d@, ich1=\E[@,
+//       il=\E[%p1%dL, il1=\E[L, ind=\n,
+//       initc=\E]P%p1%x%p2%{255}%*%{1000}%/%02x%p3%{255}%*%{1000}%/%02x%p4%{255}%*%{1000}%/%02x,
+//       nel=\r\n, oc=\E]R, op=\E[39;49m, rc=\E8, rev=\E[7m, ri=\EM,
+//       rmir=\E[4l, rmso=\E[27m, rmul=\E[24m, sc=\E7, setab=\E[4%p1%dm,
+//       setaf=\E[3%p1%dm,
+//       sgr=\E[0;10%?%p1%t;7%;%?%p2%t;4%;%?%p3%t;7%;%?%p4%t;5%;%?%p5%t;2%;%?%p6%t;1%;m%?%p9%t\016%e\017%;,
+//       sgr0=\E[m\017, smir=\E[4h, smso=\E[7m, smul=\E[4m,
+//       vpa=\E[%i%p1%dd,
+pub static LinuxEntry: [u8, ..1304] = [
+    0x1a, 0x01, 0x1f, 0x00, 0x1d, 0x00, 0x10, 0x00, 0x69, 0x01, 0xde, 0x01,
+    0x6c, 0x69, 0x6e, 0x75, 0x78, 0x7c, 0x6c, 0x69, 0x6e, 0x75, 0x78, 0x20,
+    0x63, 0x6f, 0x6e, 0x73, 0x6f, 0x6c, 0x65, 0x2c, 0x20, 0x66, 0x6f, 0x72,
+    0x20, 0x74, 0x65, 0x73, 0x74, 0x73, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x08, 0x00, 0xff, 0xff, 0x12, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x00, 0x02, 0x00, 0xff, 0xff, 0x13, 0x00, 0xff, 0xff, 0x1a, 0x00,
+    0x1e, 0x00, 0xff, 0xff, 0x29, 0x00, 0x3a, 0x00, 0x3c, 0x00, 0x40, 0x00,
+    0x4c, 0x00, 0xff, 0xff, 0x4e, 0x00, 0x5a, 0x00, 0xff, 0xff, 0x5e, 0x00,
+    0xff, 0xff, 0x62, 0x00, 0x66, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x6a, 0x00, 0x6f, 0x00, 0xff, 0xff, 0xff, 0xff, 0x74, 0x00, 0x79, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0x7e, 0x00, 0x83, 0x00, 0x88, 0x00, 0x8d, 0x00,
+    0xff, 0xff, 0x96, 0x00, 0xff, 0xff, 0xff, 0xff, 0x9b, 0x00, 0xa0, 0x00,
+    0xa6, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xac, 0x00, 0xb0, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xb4, 0x00,
+    0xff, 0xff, 0xb7, 0x00, 0xc0, 0x00, 0xc9, 0x00, 0xd2, 0x00, 0xff, 0xff,
+    0xdb, 0x00, 0xe4, 0x00, 0xed, 0x00, 0xff, 0xff, 0xf6, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x02, 0x01,
+    0x0d, 0x01, 0x10, 0x01, 0x12, 0x01, 0x15, 0x01, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x6c, 0x01, 0x75, 0x01, 0x79, 0x01, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xca, 0x01, 0xd4, 0x01, 0x0d, 0x00,
+    0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25, 0x70,
+    0x32, 0x25, 0x64, 0x72, 0x00, 0x1b, 0x5b, 0x48, 0x1b, 0x5b, 0x4a, 0x00,
+    0x1b, 0x5b, 0x4a, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25,
+    0x64, 0x47, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64,
+    0x3b, 0x25, 0x70, 0x32, 0x25, 0x64, 0x48, 0x00, 0x0a, 0x00, 0x1b, 0x5b,
+    0x48, 0x00, 0x1b, 0x5b, 0x3f, 0x32, 0x35, 0x6c, 0x1b, 0x5b, 0x3f, 0x31,
+    0x63, 0x00, 0x08, 0x00, 0x1b, 0x5b, 0x3f, 0x32, 0x35, 0x68, 0x1b, 0x5b,
+    0x3f, 0x30, 0x63, 0x00, 0x1b, 0x5b, 0x43, 0x00, 0x1b, 0x5b, 0x41, 0x00,
+    0x1b, 0x5b, 0x50, 0x00, 0x1b, 0x5b, 0x4d, 0x00, 0x1b, 0x5b, 0x35, 0x6d,
+    0x00, 0x1b, 0x5b, 0x31, 0x6d, 0x00, 0x1b, 0x5b, 0x32, 0x6d, 0x00, 0x1b,
+    0x5b, 0x34, 0x68, 0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x37,
+    0x6d, 0x00, 0x1b, 0x5b, 0x34, 0x6d, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31,
+    0x25, 0x64, 0x58, 0x00, 0x1b, 0x5b, 0x6d, 0x0f, 0x00, 0x1b, 0x5b, 0x34,
+    0x6c, 0x00, 0x1b, 0x5b, 0x32, 0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x32, 0x34,
+    0x6d, 0x00, 0x1b, 0x5b, 0x40, 0x00, 0x1b, 0x5b, 0x4c, 0x00, 0x0d, 0x0a,
+    0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x50, 0x00, 0x1b, 0x5b,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x4d, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31,
+    0x25, 0x64, 0x42, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x40,
+    0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x4c, 0x00, 0x1b, 0x5b,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x44, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31,
+    0x25, 0x64, 0x43, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x41,
+    0x00, 0x1b, 0x38, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25,
+    0x64, 0x64, 0x00, 0x1b, 0x37, 0x00, 0x0a, 0x00, 0x1b, 0x4d, 0x00, 0x1b,
+    0x5b, 0x30, 0x3b, 0x31, 0x30, 0x25, 0x3f, 0x25, 0x70, 0x31, 0x25, 0x74,
+    0x3b, 0x37, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x32, 0x25, 0x74, 0x3b,
+    0x34, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x33, 0x25, 0x74, 0x3b, 0x37,
+    0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x34, 0x25, 0x74, 0x3b, 0x35, 0x25,
+    0x3b, 0x25, 0x3f, 0x25, 0x70, 0x35, 0x25, 0x74, 0x3b, 0x32, 0x25, 0x3b,
+    0x25, 0x3f, 0x25, 0x70, 0x36, 0x25, 0x74, 0x3b, 0x31, 0x25, 0x3b, 0x6d,
+    0x25, 0x3f, 0x25, 0x70, 0x39, 0x25, 0x74, 0x0e, 0x25, 0x65, 0x0f, 0x25,
+    0x3b, 0x00, 0x1b, 0x5b, 0x33, 0x39, 0x3b, 0x34, 0x39, 0x6d, 0x00, 0x1b,
+    0x5d, 0x52, 0x00, 0x1b, 0x5d, 0x50, 0x25, 0x70, 0x31, 0x25, 0x78, 0x25,
+    0x70, 0x32, 0x25, 0x7b, 0x32, 0x35, 0x35, 0x7d, 0x25, 0x2a, 0x25, 0x7b,
+    0x31, 0x30, 0x30, 0x30, 0x7d, 0x25, 0x2f, 0x25, 0x30, 0x32, 0x78, 0x25,
+    0x70, 0x33, 0x25, 0x7b, 0x32, 0x35, 0x35, 0x7d, 0x25, 0x2a, 0x25, 0x7b,
+    0x31, 0x30, 0x30, 0x30, 0x7d, 0x25, 0x2f, 0x25, 0x30, 0x32, 0x78, 0x25,
+    0x70, 0x34, 0x25, 0x7b, 0x32, 0x35, 0x35, 0x7d, 0x25, 0x2a, 0x25, 0x7b,
+    0x31, 0x30, 0x30, 0x30, 0x7d, 0x25, 0x2f, 0x25, 0x30, 0x32, 0x78, 0x00,
+    0x1b, 0x5b, 0x33, 0x25, 0x70, 0x31, 0x25, 0x64, 0x6d, 0x00, 0x1b, 0x5b,
+    0x34, 0x25, 0x70, 0x31, 0x25, 0x64, 0x6d, 0x00,
+];
+
+// compiled with tic from:
+//   ansi|ansi/pc-term compatible, for tests,
+//       am, msgr,
+//       blink=\E[5m, bold=\E[1m, clear=\E[H\E[J, cr=\r, cub=\E[%p1%dD,
+//       cub1=\E[D, cud=\E[%p1%dB, cud1=\E[B, cuf=\E[%p1%dC, cuf1=\E[C,
+//       cup=\E[%i%p1%d;%p2%dH, cuu=\E[%p1%dA, cuu1=\E[A, dch=\E[%p1%dP,
+//       dch1=\E[P, dl=\E[%p1%dM, dl1=\E[M, ech=\E[%p1%dX, ed=\E[J,
+//       home=\E[H, hpa=\E[%i%p1%dG, ich=\E[%p1%d@, il=\E[%p1%dL, il1=\E[L,
+//       ind=\n, indn=\E[%p1%dS, invis=\E[8m, nel=\r\E[S, rev=\E[7m,
+//       rin=\E[%p1%dT, rmso=\E[m, rmul=\E[m,
+//       sgr=\E[0;10%?%p1%t;7%;%?%p2%t;4%;%?%p3%t;7%;%?%p4%t;5%;%?%p6%t;1%;%?%p7%t;8%;%?%p9%t;11%;m,
+//       sgr0=\E[0;10m, smso=\E[7m, smul=\E[4m, vpa=\E[%i%p1%dd,
+pub static AnsiEntry: [u8, ..654] = [
+    0x1a, 0x01, 0x28, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x84, 0x00, 0x42, 0x01,
+    0x61, 0x6e, 0x73, 0x69, 0x7c, 0x61, 0x6e, 0x73, 0x69, 0x2f, 0x70, 0x63,
+    0x2d, 0x74, 0x65, 0x72, 0x6d, 0x20, 0x63, 0x6f, 0x6d, 0x70, 0x61, 0x74,
+    0x69, 0x62, 0x6c, 0x65, 0x2c, 0x20, 0x66, 0x6f, 0x72, 0x20, 0x74, 0x65,
+    0x73, 0x74, 0x73, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x02, 0x00, 0xff, 0xff, 0x09, 0x00,
+    0x0d, 0x00, 0xff, 0xff, 0x18, 0x00, 0x29, 0x00, 0x2d, 0x00, 0xff, 0xff,
+    0x31, 0x00, 0xff, 0xff, 0xff, 0xff, 0x35, 0x00, 0xff, 0xff, 0x39, 0x00,
+    0xff, 0xff, 0x3d, 0x00, 0x41, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x45, 0x00, 0x4a, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x4f, 0x00, 0xff, 0xff, 0x54, 0x00, 0x59, 0x00, 0x5e, 0x00, 0x63, 0x00,
+    0xff, 0xff, 0x6c, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x74, 0x00,
+    0x78, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7c, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x80, 0x00,
+    0xff, 0xff, 0x85, 0x00, 0x8e, 0x00, 0x97, 0x00, 0xa0, 0x00, 0xa9, 0x00,
+    0xb2, 0x00, 0xbb, 0x00, 0xc4, 0x00, 0xcd, 0x00, 0xd6, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xdf, 0x00,
+    0xff, 0xff, 0xea, 0x00, 0xff, 0xff, 0xec, 0x00, 0x0d, 0x00, 0x1b, 0x5b,
+    0x48, 0x1b, 0x5b, 0x4a, 0x00, 0x1b, 0x5b, 0x4a, 0x00, 0x1b, 0x5b, 0x25,
+    0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x47, 0x00, 0x1b, 0x5b, 0x25, 0x69,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32, 0x25, 0x64, 0x48,
+    0x00, 0x1b, 0x5b, 0x42, 0x00, 0x1b, 0x5b, 0x48, 0x00, 0x1b, 0x5b, 0x44,
+    0x00, 0x1b, 0x5b, 0x43, 0x00, 0x1b, 0x5b, 0x41, 0x00, 0x1b, 0x5b, 0x50,
+    0x00, 0x1b, 0x5b, 0x4d, 0x00, 0x1b, 0x5b, 0x35, 0x6d, 0x00, 0x1b, 0x5b,
+    0x31, 0x6d, 0x00, 0x1b, 0x5b, 0x38, 0x6d, 0x00, 0x1b, 0x5b, 0x37, 0x6d,
+    0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x34, 0x6d, 0x00, 0x1b,
+    0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x58, 0x00, 0x1b, 0x5b, 0x30, 0x3b,
+    0x31, 0x30, 0x6d, 0x00, 0x1b, 0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x6d, 0x00,
+    0x1b, 0x5b, 0x4c, 0x00, 0x0d, 0x1b, 0x5b, 0x53, 0x00, 0x1b, 0x5b, 0x25,
+    0x70, 0x31, 0x25, 0x64, 0x50, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25,
+    0x64, 0x4d, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x42, 0x00,
+    0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x40, 0x00, 0x1b, 0x5b, 0x25,
+    0x70, 0x31, 0x25, 0x64, 0x53, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25,
+    0x64, 0x4c, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x44, 0x00,
+    0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x43, 0x00, 0x1b, 0x5b, 0x25,
+    0x70, 0x31, 0x25, 0x64, 0x54, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25,
+    0x64, 0x41, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64,
+    0x64, 0x00, 0x0a, 0x00, 0x1b, 0x5b, 0x30, 0x3b, 0x31, 0x30, 0x25, 0x3f,
+    0x25, 0x70, 0x31, 0x25, 0x74, 0x3b, 0x37, 0x25, 0x3b, 0x25, 0x3f, 0x25,
+    0x70, 0x32, 0x25, 0x74, 0x3b, 0x34, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70,
+    0x33, 0x25, 0x74, 0x3b, 0x37, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x34,
+    0x25, 0x74, 0x3b, 0x35, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x36, 0x25,
+    0x74, 0x3b, 0x31, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x37, 0x25, 0x74,
+    0x3b, 0x38, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x39, 0x25, 0x74, 0x3b,
+    0x31, 0x31, 0x25, 0x3b, 0x6d, 0x00,
+];
+
+// compiled with tic from:
+//   vt100|dec vt100, for tests,
+//       am, msgr, xenl,
+//       blink=\E[5m$<2>, bold=\E[1m$<2>, clear=\E[H\E[J$<50>, cr=\r,
+//       csr=\E[%i%p1%d;%p2%dr, cub=\E[%p1%dD, cub1=^H, cud=\E[%p1%dB,
+//       cud1=\n, cuf=\E[%p1%dC, cuf1=\E[C$<2>, cup=\E[%i%p1%d;%p2%dH$<5>,
+//       cuu=\E[%p1%dA, cuu1=\E[A$<2>, ed=\E[J$<50>, home=\E[H, ind=\n,
+//       rc=\E8, rev=\E[7m$<2>, ri=\EM$<5>, rmkx=\E[?1l\E>, rmso=\E[m$<2>,
+//       rmul=\E[m$<2>, sc=\E7,
+//       sgr=\E[0%?%p1%p6%|%t;1%;%?%p2%t;4%;%?%p1%p3%|%t;7%;%?%p4%t;5%;m%?%p9%t\016%e\017%;$<2>,
+//       sgr0=\E[m\017$<2>, smkx=\E[?1h\E=, smso=\E[7m$<2>, smul=\E[4m$<2>,
+pub static Vt100Entry: [u8, ..616] = [
+    0x1a, 0x01, 0x1b, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x84, 0x00, 0x2a, 0x01,
+    0x76, 0x74, 0x31, 0x30, 0x30, 0x7c, 0x64, 0x65, 0x63, 0x20, 0x76, 0x74,
+    0x31, 0x30, 0x30, 0x2c, 0x20, 0x66, 0x6f, 0x72, 0x20, 0x74, 0x65, 0x73,
+    0x74, 0x73, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+    0x02, 0x00, 0xff, 0xff, 0x13, 0x00, 0xff, 0xff, 0x1f, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0x28, 0x00, 0x3d, 0x00, 0x3f, 0x00, 0xff, 0xff, 0x43, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0x45, 0x00, 0xff, 0xff, 0x4d, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x55, 0x00,
+    0x5e, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x67, 0x00, 0x70, 0x00, 0x79, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0x82, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x8b, 0x00, 0x93, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x9b, 0x00, 0xa3, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xab, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xb4, 0x00, 0xbd, 0x00, 0xff, 0xff, 0xc6, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xcf, 0x00, 0xff, 0xff, 0xd2, 0x00,
+    0xd5, 0x00, 0xd7, 0x00, 0xde, 0x00, 0x0d, 0x00, 0x1b, 0x5b, 0x25, 0x69,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32, 0x25, 0x64, 0x72,
+    0x00, 0x1b, 0x5b, 0x48, 0x1b, 0x5b, 0x4a, 0x24, 0x3c, 0x35, 0x30, 0x3e,
+    0x00, 0x1b, 0x5b, 0x4a, 0x24, 0x3c, 0x35, 0x30, 0x3e, 0x00, 0x1b, 0x5b,
+    0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32, 0x25,
+    0x64, 0x48, 0x24, 0x3c, 0x35, 0x3e, 0x00, 0x0a, 0x00, 0x1b, 0x5b, 0x48,
+    0x00, 0x08, 0x00, 0x1b, 0x5b, 0x43, 0x24, 0x3c, 0x32, 0x3e, 0x00, 0x1b,
+    0x5b, 0x41, 0x24, 0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b, 0x35, 0x6d, 0x24,
+    0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b, 0x31, 0x6d, 0x24, 0x3c, 0x32, 0x3e,
+    0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x24, 0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b,
+    0x37, 0x6d, 0x24, 0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b, 0x34, 0x6d, 0x24,
+    0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b, 0x6d, 0x0f, 0x24, 0x3c, 0x32, 0x3e,
+    0x00, 0x1b, 0x5b, 0x6d, 0x24, 0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b, 0x6d,
+    0x24, 0x3c, 0x32, 0x3e, 0x00, 0x1b, 0x5b, 0x3f, 0x31, 0x6c, 0x1b, 0x3e,
+    0x00, 0x1b, 0x5b, 0x3f, 0x31, 0x68, 0x1b, 0x3d, 0x00, 0x1b, 0x5b, 0x25,
+    0x70, 0x31, 0x25, 0x64, 0x42, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25,
+    0x64, 0x44, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x43, 0x00,
+    0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x41, 0x00, 0x1b, 0x38, 0x00,
+    0x1b, 0x37, 0x00, 0x0a, 0x00, 0x1b, 0x4d, 0x24, 0x3c, 0x35, 0x3e, 0x00,
+    0x1b, 0x5b, 0x30, 0x25, 0x3f, 0x25, 0x70, 0x31, 0x25, 0x70, 0x36, 0x25,
+    0x7c, 0x25, 0x74, 0x3b, 0x31, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x32,
+    0x25, 0x74, 0x3b, 0x34, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x31, 0x25,
+    0x70, 0x33, 0x25, 0x7c, 0x25, 0x74, 0x3b, 0x37, 0x25, 0x3b, 0x25, 0x3f,
+    0x25, 0x70, 0x34, 0x25, 0x74, 0x3b, 0x35, 0x25, 0x3b, 0x6d, 0x25, 0x3f,
+    0x25, 0x70, 0x39, 0x25, 0x74, 0x0e, 0x25, 0x65, 0x0f, 0x25, 0x3b, 0x24,
+    0x3c, 0x32, 0x3e, 0x00,
+];
+
+// compiled with tic from:
+//   xterm-mono|monochrome xterm, for tests,
+//       am, msgr, xenl,
+//       bold=\E[1m, clear=\E[H\E[2J, cr=\r, csr=\E[%i%p1%d;%p2%dr,
+//       cub=\E[%p1%dD, cub1=^H, cud=\E[%p1%dB, cud1=\n, cuf=\E[%p1%dC,
+//       cuf1=\E[C, cup=\E[%i%p1%d;%p2%dH, cuu=\E[%p1%dA, cuu1=\E[A,
+//       dch=\E[%p1%dP, dch1=\E[P, dl=\E[%p1%dM, dl1=\E[M, ed=\E[J,
+//       home=\E[H, il=\E[%p1%dL, il1=\E[L, ind=\n, rc=\E8, rev=\E[7m,
+//       ri=\EM, rmcup=\E[2J\E[?47l\E8, rmir=\E[4l, rmkx=\E[?1l\E>,
+//       rmso=\E[m, rmul=\E[m, sc=\E7, sgr0=\E[m, smcup=\E7\E[?47h,
+//       smir=\E[4h, smkx=\E[?1h\E=, smso=\E[7m, smul=\E[4m,
+pub static XtermMonoEntry: [u8, ..558] = [
+    0x1a, 0x01, 0x27, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x83, 0x00, 0xe6, 0x00,
+    0x78, 0x74, 0x65, 0x72, 0x6d, 0x2d, 0x6d, 0x6f, 0x6e, 0x6f, 0x7c, 0x6d,
+    0x6f, 0x6e, 0x6f, 0x63, 0x68, 0x72, 0x6f, 0x6d, 0x65, 0x20, 0x78, 0x74,
+    0x65, 0x72, 0x6d, 0x2c, 0x20, 0x66, 0x6f, 0x72, 0x20, 0x74, 0x65, 0x73,
+    0x74, 0x73, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+    0x02, 0x00, 0xff, 0xff, 0x13, 0x00, 0xff, 0xff, 0x1b, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0x1f, 0x00, 0x30, 0x00, 0x32, 0x00, 0xff, 0xff, 0x36, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0x38, 0x00, 0xff, 0xff, 0x3c, 0x00, 0xff, 0xff,
+    0x40, 0x00, 0x44, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x48, 0x00, 0x4d, 0x00, 0xff, 0xff, 0xff, 0xff, 0x56, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0x5b, 0x00, 0x60, 0x00, 0x65, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0x6a, 0x00, 0x6e, 0x00, 0xff, 0xff, 0x7b, 0x00, 0x80, 0x00, 0x84, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0x88, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x8c, 0x00, 0x94, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x9c, 0x00, 0xa5, 0x00, 0xae, 0x00, 0xff, 0xff, 0xff, 0xff, 0xb7, 0x00,
+    0xc0, 0x00, 0xc9, 0x00, 0xff, 0xff, 0xd2, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xdb, 0x00, 0xff, 0xff, 0xde, 0x00,
+    0xe1, 0x00, 0xe3, 0x00, 0x0d, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70,
+    0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32, 0x25, 0x64, 0x72, 0x00, 0x1b,
+    0x5b, 0x48, 0x1b, 0x5b, 0x32, 0x4a, 0x00, 0x1b, 0x5b, 0x4a, 0x00, 0x1b,
+    0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32,
+    0x25, 0x64, 0x48, 0x00, 0x0a, 0x00, 0x1b, 0x5b, 0x48, 0x00, 0x08, 0x00,
+    0x1b, 0x5b, 0x43, 0x00, 0x1b, 0x5b, 0x41, 0x00, 0x1b, 0x5b, 0x50, 0x00,
+    0x1b, 0x5b, 0x4d, 0x00, 0x1b, 0x5b, 0x31, 0x6d, 0x00, 0x1b, 0x37, 0x1b,
+    0x5b, 0x3f, 0x34, 0x37, 0x68, 0x00, 0x1b, 0x5b, 0x34, 0x68, 0x00, 0x1b,
+    0x5b, 0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x34,
+    0x6d, 0x00, 0x1b, 0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x32, 0x4a, 0x1b, 0x5b,
+    0x3f, 0x34, 0x37, 0x6c, 0x1b, 0x38, 0x00, 0x1b, 0x5b, 0x34, 0x6c, 0x00,
+    0x1b, 0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x4c, 0x00,
+    0x1b, 0x5b, 0x3f, 0x31, 0x6c, 0x1b, 0x3e, 0x00, 0x1b, 0x5b, 0x3f, 0x31,
+    0x68, 0x1b, 0x3d, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x50,
+    0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x4d, 0x00, 0x1b, 0x5b,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x42, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31,
+    0x25, 0x64, 0x4c, 0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x44,
+    0x00, 0x1b, 0x5b, 0x25, 0x70, 0x31, 0x25, 0x64, 0x43, 0x00, 0x1b, 0x5b,
+    0x25, 0x70, 0x31, 0x25, 0x64, 0x41, 0x00, 0x1b, 0x38, 0x00, 0x1b, 0x37,
+    0x00, 0x0a, 0x00, 0x1b, 0x4d, 0x00,
+];
+
+// compiled with tic from:
+//   pcansi|ibm-pc ansi terminal programs, for tests,
+//       am, msgr,
+//       blink=\E[5m, bold=\E[1m, clear=\E[H\E[J, cr=\r, cub1=\E[D,
+//       cud1=\E[B, cuf1=\E[C, cup=\E[%i%p1%d;%p2%dH, cuu1=\E[A, dch1=\E[P,
+//       dl1=\E[M, ed=\E[J, home=\E[H, il1=\E[L, ind=\n, invis=\E[8m,
+//       rev=\E[7m, rmso=\E[m, rmul=\E[m,
+//       sgr=\E[0;10%?%p1%t;7%;%?%p2%t;4%;%?%p3%t;7%;%?%p4%t;5%;%?%p6%t;1%;%?%p7%t;8%;%?%p9%t;12%;m,
+//       sgr0=\E[0;10m, smso=\E[7m, smul=\E[4m,
+pub static PcansiEntry: [u8, ..536] = [
+    0x1a, 0x01, 0x30, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x84, 0x00, 0xc4, 0x00,
+    0x70, 0x63, 0x61, 0x6e, 0x73, 0x69, 0x7c, 0x69, 0x62, 0x6d, 0x2d, 0x70,
+    0x63, 0x20, 0x61, 0x6e, 0x73, 0x69, 0x20, 0x74, 0x65, 0x72, 0x6d, 0x69,
+    0x6e, 0x61, 0x6c, 0x20, 0x70, 0x72, 0x6f, 0x67, 0x72, 0x61, 0x6d, 0x73,
+    0x2c, 0x20, 0x66, 0x6f, 0x72, 0x20, 0x74, 0x65, 0x73, 0x74, 0x73, 0x00,
+    0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0x02, 0x00, 0xff, 0xff, 0x09, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0x0d, 0x00, 0x1e, 0x00, 0x22, 0x00, 0xff, 0xff, 0x26, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0x2a, 0x00, 0xff, 0xff, 0x2e, 0x00, 0xff, 0xff, 0x32, 0x00,
+    0x36, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3a, 0x00, 0x3f, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x44, 0x00, 0xff, 0xff,
+    0x49, 0x00, 0x4e, 0x00, 0x53, 0x00, 0xff, 0xff, 0xff, 0xff, 0x58, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x60, 0x00, 0x64, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x68, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x6c, 0x00,
+    0xff, 0xff, 0x6e, 0x00, 0x0d, 0x00, 0x1b, 0x5b, 0x48, 0x1b, 0x5b, 0x4a,
+    0x00, 0x1b, 0x5b, 0x4a, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31,
+    0x25, 0x64, 0x3b, 0x25, 0x70, 0x32, 0x25, 0x64, 0x48, 0x00, 0x1b, 0x5b,
+    0x42, 0x00, 0x1b, 0x5b, 0x48, 0x00, 0x1b, 0x5b, 0x44, 0x00, 0x1b, 0x5b,
+    0x43, 0x00, 0x1b, 0x5b, 0x41, 0x00, 0x1b, 0x5b, 0x50, 0x00, 0x1b, 0x5b,
+    0x4d, 0x00, 0x1b, 0x5b, 0x35, 0x6d, 0x00, 0x1b, 0x5b, 0x31, 0x6d, 0x00,
+    0x1b, 0x5b, 0x38, 0x6d, 0x00, 0x1b, 0x5b, 0x37, 0x6d, 0x00, 0x1b, 0x5b,
+    0x37, 0x6d, 0x00, 0x1b, 0x5b, 0x34, 0x6d, 0x00, 0x1b, 0x5b, 0x30, 0x3b,
+    0x31, 0x30, 0x6d, 0x00, 0x1b, 0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x6d, 0x00,
+    0x1b, 0x5b, 0x4c, 0x00, 0x0a, 0x00, 0x1b, 0x5b, 0x30, 0x3b, 0x31, 0x30,
+    0x25, 0x3f, 0x25, 0x70, 0x31, 0x25, 0x74, 0x3b, 0x37, 0x25, 0x3b, 0x25,
+    0x3f, 0x25, 0x70, 0x32, 0x25, 0x74, 0x3b, 0x34, 0x25, 0x3b, 0x25, 0x3f,
+    0x25, 0x70, 0x33, 0x25, 0x74, 0x3b, 0x37, 0x25, 0x3b, 0x25, 0x3f, 0x25,
+    0x70, 0x34, 0x25, 0x74, 0x3b, 0x35, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70,
+    0x36, 0x25, 0x74, 0x3b, 0x31, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x37,
+    0x25, 0x74, 0x3b, 0x38, 0x25, 0x3b, 0x25, 0x3f, 0x25, 0x70, 0x39, 0x25,
+    0x74, 0x3b, 0x31, 0x32, 0x25, 0x3b, 0x6d, 0x00,
+];
+
+// compiled with tic from:
+//   dumb|80-column dumb tty, for tests,
+//       am,
+//       cr=\r, cud1=\n, ind=\n,
+pub static DumbEntry: [u8, ..316] = [
+    0x1a, 0x01, 0x23, 0x00, 0x02, 0x00, 0x00, 0x00, 0x82, 0x00, 0x06, 0x00,
+    0x64, 0x75, 0x6d, 0x62, 0x7c, 0x38, 0x30, 0x2d, 0x63, 0x6f, 0x6c, 0x75,
+    0x6d, 0x6e, 0x20, 0x64, 0x75, 0x6d, 0x62, 0x20, 0x74, 0x74, 0x79, 0x2c,
+    0x20, 0x66, 0x6f, 0x72, 0x20, 0x74, 0x65, 0x73, 0x74, 0x73, 0x00, 0x00,
+    0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x02, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x04, 0x00, 0x0d, 0x00,
+    0x0a, 0x00, 0x0a, 0x00,
+];
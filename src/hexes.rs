@@ -1,12 +1,20 @@
+use color;
+use color::{Color, DefaultColor, RgbColor};
 use info;
-use ios::{cooked,cbreak,echo};
+use info::TermInfo;
+use ios;
+use ios::{cbreak,echo};
 use trie::Trie;
-use std::{str, uint, iter, io};
-use std::io::File;
-use std::path::posix::Path;
+use std::{cmp, fmt, mem, os, str, uint, iter};
+use std::io::IoError;
+use std::sync::Arc;
 
 use util;
 
+#[cfg(test)]
+use fixtures::{AnsiEntry, DumbEntry, LinuxEntry, PcansiEntry, Vt100Entry,
+               XtermEntry, XtermMonoEntry};
+
 /// Keys that can be returned by `Term::read`.
 #[deriving(PartialEq, Show)]
 pub enum Keypress {
@@ -27,10 +35,213 @@ pub enum Keypress {
     KeyEscape,
 }
 
+/// The modifier keys that were held down during a keypress.
+#[deriving(PartialEq, Eq, Show)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// No modifier keys at all.
+    pub fn none () -> Modifiers {
+        Modifiers { shift: false, alt: false, ctrl: false, meta: false }
+    }
+
+    /// Whether no modifier keys were held down.
+    pub fn is_empty (&self) -> bool {
+        !(self.shift || self.alt || self.ctrl || self.meta)
+    }
+
+    // xterm encodes modifiers as one more than a bitmask of them
+    fn from_param (n: uint) -> Modifiers {
+        let bits = n - 1;
+        Modifiers {
+            shift: bits & 1 != 0,
+            alt:   bits & 2 != 0,
+            ctrl:  bits & 4 != 0,
+            meta:  bits & 8 != 0,
+        }
+    }
+}
+
+/**
+ * A key, along with the modifier keys that were held down while pressing it.
+ *
+ * Control characters are still reported as `KeyCtrl` without `ctrl` set,
+ * since that is how the terminal sends them, but special keys like the
+ * arrow keys have their modifiers in `mods`.
+ */
+#[deriving(PartialEq, Show)]
+pub struct Key {
+    pub code: Keypress,
+    pub mods: Modifiers,
+}
+
+/// The mouse buttons that can be reported in a `Mouse` event.
+#[deriving(PartialEq, Eq, Show)]
+pub enum MouseButton {
+    MouseLeft,
+    MouseMiddle,
+    MouseRight,
+    MouseWheelUp,
+    MouseWheelDown,
+    MouseWheelLeft,
+    MouseWheelRight,
+    /// Used for motion without any buttons held, and releases in the
+    /// encodings which don't say which button was released.
+    MouseNoButton,
+}
+
+/// What happened to the mouse in a `Mouse` event.
+#[deriving(PartialEq, Eq, Show)]
+pub enum MouseAction {
+    MousePress,
+    MouseRelease,
+    /// The mouse was moved with a button held down.
+    MouseDrag,
+    /// The mouse was moved without any buttons held down.
+    MouseMotion,
+}
+
+/**
+ * A mouse event.
+ *
+ * The position is in the same (0-based) coordinates as `move_cursor` uses.
+ * The wheel is reported as presses of the wheel buttons.
+ */
+#[deriving(PartialEq, Show)]
+pub struct Mouse {
+    pub button: MouseButton,
+    pub action: MouseAction,
+    pub col: uint,
+    pub row: uint,
+    pub mods: Modifiers,
+}
+
+/// Which mouse events the terminal should report (see `Term::mouse`).
+#[deriving(PartialEq, Eq, Show)]
+pub enum MouseMode {
+    /// Only button presses and releases.
+    MouseClicks = 1000,
+    /// Presses, releases, and motion while a button is held down.
+    MouseDrags = 1002,
+    /// Presses, releases, and all motion.
+    MouseAllMotion = 1003,
+}
+
+/// Events that can be returned by `Term::read`.
+#[deriving(PartialEq, Show)]
+pub enum Event {
+    KeyEvent(Key),
+    MouseEvent(Mouse),
+    /// Text which was pasted while bracketed paste mode was enabled.
+    PasteEvent(String),
+    /// The terminal was resized to (`cols`, `rows`).
+    ResizeEvent(uint, uint),
+    /// The timer with this id (from `Term::add_timer`) went off.
+    TimerEvent(uint),
+    /// The descriptor (from `Term::watch_fd`) has input to read.
+    FdEvent(int),
+    /// A `Waker` was woken.
+    WakeupEvent,
+    /**
+     * The process was suspended with ^Z and has been continued, so the
+     * screen needs to be redrawn.
+     */
+    ResumeEvent,
+}
+
+/**
+ * A handle for interrupting `Term::read` from other tasks.
+ *
+ * Calling `wake` makes `read` return a `WakeupEvent`. Several wakeups
+ * before the next `read` may only produce a single event.
+ */
+#[deriving(Clone)]
+pub struct Waker {
+    pipe: Arc<WakeupPipe>,
+}
+
+impl Waker {
+    /// Makes `Term::read` return a `WakeupEvent`.
+    pub fn wake (&self) {
+        util::wakeup(self.pipe.fd);
+    }
+}
+
+// the write end of a TermReader's wakeup pipe, which stays open for as long
+// as any of its Wakers are around
+struct WakeupPipe {
+    fd: int,
+}
+
+impl Drop for WakeupPipe {
+    fn drop (&mut self) {
+        if self.fd >= 0 {
+            util::close(self.fd);
+        }
+    }
+}
+
+/// How an output capability is provided on a particular terminal.
+#[deriving(PartialEq, Eq, Show)]
+pub enum Support {
+    /// The terminal has the capability itself.
+    Native,
+    /// The capability is synthesized from other capabilities.
+    Emulated,
+    /// The capability is neither available nor emulated.
+    Unsupported,
+}
+
+/// The ways in which setting up a `Term` can fail.
+pub enum Error {
+    /// The terminal's capabilities couldn't be loaded, or aren't enough.
+    InfoError(info::Error),
+    /// The terminal device couldn't be opened.
+    TtyError(IoError),
+}
+
+impl fmt::Show for Error {
+    fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InfoError(ref e) => write!(f, "{}", e),
+            TtyError(ref e)  => write!(f, "Couldn't open the terminal: {}", e),
+        }
+    }
+}
+
+/// The result of setting up a `Term`.
+pub type TermResult<T> = Result<T, Error>;
+
+// there's no point in going on without a way to draw things
+fn check_caps (info: &TermInfo) -> TermResult<()> {
+    for &cap in ["clear", "cup"].iter() {
+        if support(info, cap) == Unsupported {
+            let e = info::UnsupportedCapability(cap.to_string());
+            return Err(InfoError(e));
+        }
+    }
+    Ok(())
+}
+
+/**
+ * A terminal being used for full screen output.
+ *
+ * When this is dropped, the terminal is put back the way it was found: the
+ * modes and attributes that were turned on are turned off, and the termios
+ * settings are restored. This also happens when the task fails.
+ */
 pub struct Term {
     // XXX: either use accessors or rename
     pub r: TermReader,
     pub w: TermWriter,
+    // the settings from before we changed anything
+    termios: Option<ios::Termios>,
+    catch_signals: bool,
 }
 
 impl Term {
@@ -38,34 +249,156 @@ impl Term {
     * Creates a new `Term` instance.
     *
     * This can be used to manipulate the terminal for full screen
-    * applications.
+    * applications. If the terminal can't be used that way, an error is
+    * returned, and the application should fall back to plain line output.
     */
-    pub fn new () -> Term {
-        info::init();
+    pub fn new () -> TermResult<Term> {
+        match TermInfo::from_env() {
+            Ok(info) => Term::with_info(info),
+            Err(e)   => Err(InfoError(e)),
+        }
+    }
+
+    /**
+     * Creates a new `Term` instance for a specific type of terminal.
+     *
+     * This is like `new`, except that the capabilities in `info` are used
+     * instead of looking up the terminal named by the `TERM` environment
+     * variable.
+     */
+    pub fn with_info (info: TermInfo) -> TermResult<Term> {
+        try!(check_caps(&info));
+
+        let fd = match ios::open_tty() {
+            Ok(fd) => fd,
+            Err(e) => return Err(TtyError(e)),
+        };
+        util::watch_resize();
+        util::watch_suspend(true);
+        Ok(Term::from_fd(info, fd, true))
+    }
+
+    /**
+     * Creates a new `Term` instance which uses the terminal `fd`, such as a
+     * pty master or a socket, instead of the controlling terminal.
+     *
+     * `fd` is used for both input and output, and is left open when the
+     * `Term` is dropped. If it isn't a terminal, it is up to the caller to
+     * tell the `Term` its size with `resize`, and no `ResizeEvent`s are
+     * returned.
+     */
+    pub fn with_fd (info: TermInfo, fd: int) -> TermResult<Term> {
+        try!(check_caps(&info));
+        Ok(Term::from_fd(info, fd, false))
+    }
 
-        cbreak();
-        echo(false);
+    /**
+     * Creates a new `Term` instance which reads from `input` and writes to
+     * `output`, like an in-memory buffer in tests.
+     *
+     * Since there's no descriptor to wait on, `read` just blocks on `input`,
+     * so timers and watched descriptors don't do anything, and an escape
+     * key is only told apart from an escape sequence by what comes after
+     * it. The size has to be set with `resize`.
+     */
+    pub fn with_streams (info: TermInfo, input: Box<Reader + Send>,
+                         output: Box<Writer + Send>) -> TermResult<Term> {
+        try!(check_caps(&info));
+        Ok(Term::start(info, StreamSource(input), output, 0, 0, None))
+    }
 
-        // XXX: this sounds like hack
-        let mut stream = io::File::open_mode(&Path::new("/dev/tty"), io::Open,
-                io::ReadWrite).ok().expect("Open tty");
+    fn from_fd (info: TermInfo, fd: int, owned: bool) -> Term {
+        let termios = ios::tcgetattr(fd).ok();
+        cbreak(fd);
+        echo(fd, false);
+        let (cols, rows) = ios::size(fd);
+        let output = box util::FdWriter::new(fd) as Box<Writer + Send>;
+        Term::start(info, FdSource(fd, owned), output, cols, rows, termios)
+    }
 
+    fn start (info: TermInfo, source: Source, mut output: Box<Writer + Send>,
+              cols: uint, rows: uint, termios: Option<ios::Termios>) -> Term {
         // XXX need to come up with a better way to handle optional caps
         // should be able to use something like has_keypad_xmit or something
         let terms = ["smkx", /* "smcup", */ "sgr0", "cnorm"];
         for &cap in terms.iter() {
-            match info::escape(cap) {
-                Some(e) => { stream.write_str(e.as_slice());  }
-                None    => (), // not a big deal if these don't exist
+            match info.escape(cap) {
+                Ok(Some(e)) => { output.write_str(e.as_slice()); }
+                _           => (), // not a big deal if these don't exist
             }
         }
 
         Term {
-            r: TermReader::new(),
-            w: TermWriter::new(stream),
+            r: TermReader::new(&info, source),
+            w: TermWriter::new(output, info, cols, rows),
+            termios: termios,
+            catch_signals: false,
+        }
+    }
+
+    /**
+     * Makes the terminal get restored if the process is killed by SIGTERM,
+     * SIGINT, SIGHUP or SIGQUIT.
+     *
+     * Dropping the `Term` doesn't happen then, so without this, the terminal
+     * would be left in whatever state the application put it in. The signal
+     * is still handled as it would have been otherwise afterwards. Only one
+     * `Term` can do this at a time, and it stops when it is dropped. This
+     * does nothing for a `Term` created with `with_streams`.
+     */
+    pub fn restore_on_signal (&mut self) {
+        if self.r.fd().is_none() {
+            return;
+        }
+        self.catch_signals = true;
+        self.update_restore();
+        util::catch_fatal(true);
+    }
+
+    // keeps what the signal handler does in sync with the modes in effect
+    fn update_restore (&self) {
+        if !self.catch_signals {
+            return;
+        }
+        match self.r.fd() {
+            Some(fd) => {
+                let seq = self.w.restore_sequence();
+                util::set_restore(fd, seq.as_slice(), self.termios.as_ref());
+            }
+            None     => (),
         }
     }
 
+    /// The capabilities of the terminal being used.
+    pub fn info<'a> (&'a self) -> &'a TermInfo {
+        &self.w.info
+    }
+
+    /**
+     * Whether the output capability `name` is available.
+     *
+     * Some capabilities which the terminal doesn't have are emulated with
+     * the ones it does have, such as `indn` by repeating `ind`, or `rmul` by
+     * resetting all attributes and turning the others back on. These work
+     * the same way, but may produce more output. Operations which are
+     * `Unsupported` don't write anything.
+     */
+    pub fn support (&self, name: &str) -> Support {
+        self.w.support(name)
+    }
+
+    /**
+     * Tells the `Term` that the terminal is now `cols` columns by `rows`
+     * rows.
+     *
+     * This is done automatically when a `ResizeEvent` is read, so it is only
+     * needed for terminals which can't report that themselves, like a remote
+     * client which sends its size some other way.
+     */
+    pub fn resize (&mut self, cols: uint, rows: uint) {
+        self.w.resize(cols, rows);
+    }
+
     /// Clears the screen.
     pub fn clear (&mut self) {
         self.w.clear();
@@ -76,6 +409,19 @@ impl Term {
         self.w.move_cursor(col, row);
     }
 
+    /**
+     * Moves the cursor to (`col`, `row`), knowing that `text` is what is
+     * currently on the screen between the cursor and `col`.
+     *
+     * `text` must be drawn with the current attributes. This lets the
+     * cursor be moved right by just writing `text` again, if that is
+     * cheaper than using an escape.
+     */
+    pub fn move_cursor_over (&mut self, col: uint, row: uint,
+                             text: Option<&str>) {
+        self.w.move_cursor_over(col, row, text);
+    }
+
     /// Scrolls the text up.
     pub fn scroll_forward (&mut self, lines: uint) {
         self.w.scroll_forward(lines);
@@ -86,16 +432,89 @@ impl Term {
         self.w.scroll_reverse(lines);
     }
 
-    /// Changes the currently active foreground color to `color`.
-    pub fn fg_color (&mut self, color: info::Color) {
+    /**
+     * Makes scrolling only move the rows from `top` to `bottom`, inclusive.
+     *
+     * This affects `scroll_forward` and `scroll_reverse`, newlines at the
+     * bottom of the region, and inserting and deleting lines. The cursor
+     * may be moved by this, so it should be moved somewhere before writing.
+     * On terminals without `csr`, the region is kept by deleting and
+     * inserting lines instead, which needs the cursor position to be known.
+     * That only covers `scroll_forward`, `scroll_reverse`, `insert_lines` and
+     * `delete_lines`, and a newline at the bottom of the region still
+     * scrolls the whole screen, so `csr` is reported as unsupported there. A
+     * region which is empty or goes past the bottom of the screen is
+     * ignored.
+     */
+    pub fn set_scroll_region (&mut self, top: uint, bottom: uint) {
+        self.w.set_scroll_region(top, bottom);
+    }
+
+    /// Makes scrolling move the whole screen again.
+    pub fn reset_scroll_region (&mut self) {
+        self.w.reset_scroll_region();
+    }
+
+    /**
+     * Inserts `n` blank lines at the cursor, moving the ones below it down.
+     *
+     * Lines which are pushed past the bottom of the scroll region are lost.
+     */
+    pub fn insert_lines (&mut self, n: uint) {
+        self.w.insert_lines(n);
+    }
+
+    /**
+     * Deletes `n` lines at the cursor, moving the ones below it up.
+     *
+     * Blank lines are added at the bottom of the scroll region.
+     */
+    pub fn delete_lines (&mut self, n: uint) {
+        self.w.delete_lines(n);
+    }
+
+    /// Inserts `n` blanks at the cursor, moving the rest of the line right.
+    pub fn insert_chars (&mut self, n: uint) {
+        self.w.insert_chars(n);
+    }
+
+    /// Deletes `n` characters at the cursor, moving the rest of the line left.
+    pub fn delete_chars (&mut self, n: uint) {
+        self.w.delete_chars(n);
+    }
+
+    /// Blanks out `n` characters at the cursor, without moving anything.
+    pub fn erase_chars (&mut self, n: uint) {
+        self.w.erase_chars(n);
+    }
+
+    /**
+     * Changes the currently active foreground color to `color`.
+     *
+     * Colors which the terminal can't show are replaced with the closest one
+     * it has, and are ignored entirely if `NO_COLOR` is set. 24-bit colors
+     * can only be shown if the terminal says it supports them, either with
+     * the `RGB` or `Tc` capabilities, or with `COLORTERM=truecolor`.
+     */
+    pub fn fg_color (&mut self, color: Color) {
         self.w.fg_color(color);
     }
 
     /// Changes the currently active background color to `color`.
-    pub fn bg_color (&mut self, color: info::Color) {
+    pub fn bg_color (&mut self, color: Color) {
         self.w.bg_color(color);
     }
 
+    /**
+     * Changes how colors that the terminal doesn't have are replaced.
+     *
+     * The default is `PerceptualMapping`, which picks the closest looking
+     * color.
+     */
+    pub fn color_mapping (&mut self, mapping: color::Mapping) {
+        self.w.color_mapping(mapping);
+    }
+
     /// Resets the foreground and background colors to the default.
     pub fn reset_color (&mut self) {
         self.w.reset_color();
@@ -106,6 +525,25 @@ impl Term {
         self.w.underline(enabled);
     }
 
+    /**
+     * Changes how underlines are drawn.
+     *
+     * Terminals without the `Smulx` capability draw every style as a
+     * single underline.
+     */
+    pub fn underline_style (&mut self, style: UnderlineStyle) {
+        self.w.underline_style(style);
+    }
+
+    /**
+     * Changes the color of underlines, separately from the text.
+     *
+     * This is ignored on terminals without the `Setulc` capability.
+     */
+    pub fn underline_color (&mut self, color: Color) {
+        self.w.underline_color(color);
+    }
+
     /// Enables or disables standout mode.
     pub fn standout (&mut self, enabled: bool) {
         self.w.standout(enabled);
@@ -126,9 +564,65 @@ impl Term {
         self.w.blink(enabled);
     }
 
+    /**
+     * Enables or disables italic mode.
+     *
+     * This and the attributes below aren't available on every terminal, and
+     * are ignored on the ones that don't have them.
+     */
+    pub fn italic (&mut self, enabled: bool) {
+        self.w.italic(enabled);
+    }
+
+    /// Enables or disables dim (half-bright) mode.
+    pub fn dim (&mut self, enabled: bool) {
+        self.w.dim(enabled);
+    }
+
+    /// Enables or disables invisible mode.
+    pub fn invisible (&mut self, enabled: bool) {
+        self.w.invisible(enabled);
+    }
+
+    /// Enables or disables strikethrough mode.
+    pub fn strikethrough (&mut self, enabled: bool) {
+        self.w.strikethrough(enabled);
+    }
+
+    /// Enables or disables overline mode.
+    pub fn overline (&mut self, enabled: bool) {
+        self.w.overline(enabled);
+    }
+
+    /// Enables or disables protected mode.
+    pub fn protected (&mut self, enabled: bool) {
+        self.w.protected(enabled);
+    }
+
+    /// The colors and attributes that text is currently written with.
+    pub fn attrs (&self) -> Attrs {
+        self.w.attrs()
+    }
+
+    /**
+     * Changes all of the colors and attributes at once.
+     *
+     * This emits as little as possible to get from the current state to
+     * `attrs`, which is useful when drawing text whose attributes are
+     * stored somewhere, like in a `screen::Screen`. The change is sent as a
+     * single escape where the terminal allows it.
+     *
+     * Attributes that the terminal can't combine with colors (the ones in
+     * its `ncv` capability) are left out while colors are in use.
+     */
+    pub fn set_attrs (&mut self, attrs: &Attrs) {
+        self.w.set_attrs(attrs);
+    }
+
     /// Enables or disables visible cursor mode.
     pub fn cursor (&mut self, enabled: bool) {
         self.w.cursor(enabled);
+        self.update_restore();
     }
 
     /**
@@ -140,6 +634,67 @@ impl Term {
      */
     pub fn alternate_screen (&mut self, enabled: bool) {
         self.w.alternate_screen(enabled);
+        self.update_restore();
+    }
+
+    /**
+     * Enables or disables reporting of mouse events.
+     *
+     * With `Some(mode)`, the mouse events selected by `mode` will be
+     * returned from `read`, and `None` turns them off again. The terminal
+     * usually stops doing its own selection while this is enabled.
+     */
+    pub fn mouse (&mut self, mode: Option<MouseMode>) {
+        self.w.mouse(mode);
+        self.update_restore();
+    }
+
+    /**
+     * Enables or disables bracketed paste mode.
+     *
+     * While this is enabled, text pasted into the terminal is returned from
+     * `read` as a single `PasteEvent`, rather than as a keypress for each
+     * character, so newlines and escapes in it can't trigger anything.
+     */
+    pub fn bracketed_paste (&mut self, enabled: bool) {
+        self.w.bracketed_paste(enabled);
+        self.update_restore();
+    }
+
+    /**
+     * Starts a timer, which makes `read` return a `TimerEvent` after `ms`
+     * milliseconds.
+     *
+     * If `repeat` is true, the timer keeps going off every `ms` milliseconds
+     * until it is cancelled. Returns the id used in the events.
+     */
+    pub fn add_timer (&mut self, ms: uint, repeat: bool) -> uint {
+        self.r.add_timer(ms, repeat)
+    }
+
+    /// Stops the timer `id` from going off again.
+    pub fn cancel_timer (&mut self, id: uint) {
+        self.r.cancel_timer(id);
+    }
+
+    /**
+     * Makes `read` return an `FdEvent` whenever `fd` has input.
+     *
+     * The input is left for the caller to read, and the event will keep
+     * being returned until it has been.
+     */
+    pub fn watch_fd (&mut self, fd: int) {
+        self.r.watch_fd(fd);
+    }
+
+    /// Stops watching `fd` for input.
+    pub fn unwatch_fd (&mut self, fd: int) {
+        self.r.unwatch_fd(fd);
+    }
+
+    /// Returns a handle which can interrupt `read` from other tasks.
+    pub fn waker (&self) -> Waker {
+        self.r.waker()
     }
 
     /**
@@ -165,10 +720,14 @@ impl Term {
     }
 
     /**
-     * Read a keypress from the terminal.
+     * Read a keypress or other event from the terminal.
      *
-     * Returns `Some(Keypress)` if a key was read, and `None` if `stdin`
-     * reaches `eof`.
+     * Returns `Some(Event)` if something was read, and `None` if `stdin`
+     * reaches `eof` or can't be waited on. Mouse events are only returned
+     * after enabling them with `mouse`, and a `ResizeEvent` is returned as
+     * soon as the terminal is resized, even if no key has been pressed. The
+     * same goes for timers, watched descriptors, and wakeups, so this can be
+     * used as the main loop of an application.
      *
      * Note that most special keys are actually sequences of multiple
      * characters. This means that if a prefix of a special character key
@@ -179,239 +738,978 @@ impl Term {
      *
      * Also, other special keys are represented as control keys, so for
      * instance, `^J` will likely return `KeyReturn` instead of
-     * `KeyCtrl('j')`.
+     * `KeyCtrl('j')`. Keys pressed with alt are sent with an escape in front
+     * of them, so an escape followed quickly by another key will be read as
+     * that key with `alt` set.
+     */
+    pub fn read (&mut self) -> Option<Event> {
+        self.w.flush();
+        let e = self.r.read();
+        match e {
+            Some(ResizeEvent(cols, rows)) => self.w.resize(cols, rows),
+            Some(ResumeEvent)             => self.suspend(),
+            _                             => (),
+        }
+        e
+    }
+
+    /**
+     * Suspends the process, like pressing ^Z does.
+     *
+     * The terminal is put back the way it was found while the process is
+     * stopped, and set up again once it is continued, after which this
+     * returns, and the screen needs to be redrawn. In cbreak mode, this
+     * happens by itself when ^Z is pressed, and `read` returns a
+     * `ResumeEvent` afterwards. This does nothing unless the `Term` is
+     * using the controlling terminal.
      */
-    pub fn read (&mut self) -> Option<Keypress> {
+    pub fn suspend (&mut self) {
+        let fd = match self.r.tty_fd() {
+            Some(fd) => fd,
+            None     => return,
+        };
+
+        let modes = self.w.modes();
+        let termios = ios::tcgetattr(fd).ok();
+        self.reset();
+
+        util::suspend();
+
+        match termios.as_ref() {
+            Some(t) => { let _ = ios::tcsetattr(fd, ios::SetNow, t); }
+            None    => (),
+        }
+        let (cols, rows) = ios::size(fd);
+        self.w.resize(cols, rows);
+        self.w.set_modes(&modes);
         self.w.flush();
-        self.r.read()
+        self.update_restore();
+    }
+
+    // puts the terminal back the way it was found
+    fn reset (&mut self) {
+        self.w.restore();
+        match (self.r.fd(), self.termios.as_ref()) {
+            (Some(fd), Some(t)) => {
+                let _ = ios::tcsetattr(fd, ios::SetDrain, t);
+            }
+            _                   => (),
+        }
     }
 }
 
-// XXX - todo - reimplement, doesn't really belong in Drop
-/*
 impl Drop for Term {
     fn drop (&mut self) {
-        // XXX need to come up with a better way to handle optional caps
-        // should be able to use something like has_keypad_xmit or something
-        let terms = ["rmkx", /* "rmcup", */ "sgr0", "cnorm"];
-        for &cap in terms.iter() {
-            match info::escape(cap) {
-                Some(e) => { self.w.stream.write_str(e.as_slice()); }
-                None    => (), // not a big deal if these don't exist
-            }
+        if self.catch_signals {
+            util::catch_fatal(false);
         }
-
-        // XXX should really restore the previous termios mode...
-        echo(true);
-        cooked();
+        if self.r.tty_fd().is_some() {
+            util::watch_suspend(false);
+        }
+        self.reset();
     }
 }
-*/
 
 pub struct TermWriter {
     buf: String,
-    state: AttrState,
-    stream: File,
+    state: Attrs,
+    stream: Box<Writer + Send>,
+    info: TermInfo,
+    mouse: Option<MouseMode>,
+    paste: bool,
+    alternate: bool,
+    cursor_visible: bool,
+    palette: color::Palette,
+    // the attributes which can't be used together with colors
+    ncv: uint,
+    // where the cursor is, if we know
+    pos: Option<(uint, uint)>,
+    cols: uint,
+    rows: uint,
+    // the scroll region, if it isn't the whole screen
+    region: Option<(uint, uint)>,
+}
+
+// the things that get turned off while suspended
+struct Modes {
+    attrs: Attrs,
+    region: Option<(uint, uint)>,
+    mouse: Option<MouseMode>,
+    paste: bool,
+    alternate: bool,
+    cursor_visible: bool,
 }
 
-struct AttrState {
-    fg: Option<info::Color>,
-    bg: Option<info::Color>,
-    underline: bool,
-    standout: bool,
-    reverse: bool,
-    bold: bool,
-    blink: bool,
+/// The colors and attributes that text is drawn with.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub struct Attrs {
+    pub fg: Color,
+    pub bg: Color,
+    pub underline: bool,
+    pub underline_style: UnderlineStyle,
+    pub underline_color: Color,
+    pub standout: bool,
+    pub reverse: bool,
+    pub bold: bool,
+    pub blink: bool,
+    pub italic: bool,
+    pub dim: bool,
+    pub invisible: bool,
+    pub strikethrough: bool,
+    pub overline: bool,
+    pub protected: bool,
 }
 
-fn AttrState () -> AttrState {
-    AttrState {
-        fg: None,
-        bg: None,
-        underline: false,
-        standout: false,
-        reverse: false,
-        bold: false,
-        blink: false,
+/// The ways an underline can be drawn.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub enum UnderlineStyle {
+    SingleUnderline = 1,
+    DoubleUnderline = 2,
+    CurlyUnderline = 3,
+    DottedUnderline = 4,
+    DashedUnderline = 5,
+}
+
+impl Attrs {
+    /// The default colors, with no attributes enabled.
+    pub fn new () -> Attrs {
+        Attrs {
+            fg: DefaultColor,
+            bg: DefaultColor,
+            underline: false,
+            underline_style: SingleUnderline,
+            underline_color: DefaultColor,
+            standout: false,
+            reverse: false,
+            bold: false,
+            blink: false,
+            italic: false,
+            dim: false,
+            invisible: false,
+            strikethrough: false,
+            overline: false,
+            protected: false,
+        }
+    }
+
+    fn has_attributes (&self) -> bool {
+        self.underline || self.standout || self.reverse || self.bold
+            || self.blink || self.italic || self.dim || self.invisible
+            || self.strikethrough || self.overline || self.protected
     }
 }
 
 impl TermWriter {
-    fn new (stream: File) -> TermWriter {
+    fn new (stream: Box<Writer + Send>, info: TermInfo, cols: uint,
+            rows: uint) -> TermWriter {
+        let palette = palette(&info);
+        let ncv = match info.number("ncv") {
+            Ok(Some(n)) if n > 0 => n as uint,
+            _                    => 0,
+        };
         TermWriter {
             buf: "".to_string(),
-            state: AttrState(),
+            state: Attrs::new(),
             stream: stream,
+            info: info,
+            mouse: None,
+            paste: false,
+            alternate: false,
+            cursor_visible: true,
+            palette: palette,
+            ncv: ncv,
+            pos: None,
+            cols: cols,
+            rows: rows,
+            region: None,
         }
     }
 
+    pub fn support (&self, name: &str) -> Support {
+        support(&self.info, name)
+    }
+
     pub fn clear (&mut self) {
-        self.buf.push_str(info::clear_screen().as_slice());
+        // on terminals with bce, clearing would fill the screen with the
+        // current background color, so make it consistent with the ones
+        // without it
+        if self.state.bg != DefaultColor && self.info.back_color_erase() {
+            if self.has_cap("op") {
+                self.push_cap("op");
+                self.push_clear();
+                self.replay_colors();
+            }
+            else {
+                self.push_cap("sgr0");
+                self.push_clear();
+                self.replay_state();
+            }
+        }
+        else {
+            self.push_clear();
+        }
+        self.pos = Some((0, 0));
     }
 
     pub fn move_cursor (&mut self, col: uint, row: uint) {
-        if col == 0u && row == 0u {
-            self.buf.push_str(info::cursor_home().as_slice());
+        self.move_cursor_over(col, row, None);
+    }
+
+    pub fn move_cursor_over (&mut self, col: uint, row: uint,
+                             text: Option<&str>) {
+        if self.pos == Some((col, row)) {
+            return;
         }
-        else {
-            self.buf.push_str(info::cursor_address(row, col).as_slice());
+
+        // some terminals get confused if you move the cursor with attributes
+        // like standout enabled
+        let reset = self.state.has_attributes()
+            && !self.info.move_standout_mode();
+        if reset {
+            self.push_cap("sgr0");
+        }
+
+        // the text would be drawn without its attributes after a reset
+        let text = if reset { None } else { text };
+        self.pos = match motion(&self.info, self.pos, col, row, text) {
+            Some(m) => {
+                self.buf.push_str(m.as_slice());
+                Some((col, row))
+            }
+            // the terminal can't get there, so don't pretend the cursor did
+            None    => None,
+        };
+
+        if reset {
+            self.replay_state();
         }
     }
 
+    pub fn resize (&mut self, cols: uint, rows: uint) {
+        self.cols = cols;
+        self.rows = rows;
+        // the terminal may have moved things around, and resets the scroll
+        // region
+        self.pos = None;
+        self.region = None;
+    }
+
     pub fn scroll_forward (&mut self, lines: uint) {
+        match self.emulated_region() {
+            Some((top, bottom)) => {
+                self.in_region(top, bottom, |w| {
+                    w.move_cursor(0, top);
+                    w.delete_lines(lines);
+                });
+                return;
+            }
+            None                => (),
+        }
+        self.pos = None;
         if lines == 1 {
-            self.buf.push_str(info::scroll_forward().as_slice());
+            self.push_cap("ind");
         } else {
-            self.buf.push_str(info::scroll_forward_multiple(lines).as_slice());
+            self.push_repeated("indn", "ind", lines);
         }
     }
 
     pub fn scroll_reverse (&mut self, lines: uint) {
+        match self.emulated_region() {
+            Some((top, bottom)) => {
+                self.in_region(top, bottom, |w| {
+                    w.move_cursor(0, top);
+                    w.insert_lines(lines);
+                });
+                return;
+            }
+            None                => (),
+        }
+        self.pos = None;
         if lines == 1 {
-            self.buf.push_str(info::scroll_reverse().as_slice());
+            self.push_cap("ri");
         } else {
-            self.buf.push_str(info::scroll_reverse_multiple(lines).as_slice());
+            self.push_repeated("rin", "ri", lines);
         }
     }
 
-    pub fn fg_color (&mut self, color: info::Color) {
-        match self.state.fg {
-            Some(c) if c == color => {}
-            _                     => {
-                self.state.fg = Some(color);
-                self.buf.push_str(info::set_a_foreground(color).as_slice());
-            }
+    pub fn set_scroll_region (&mut self, top: uint, bottom: uint) {
+        if top > bottom || bottom >= self.rows {
+            return;
+        }
+        self.region = Some((top, bottom));
+        if self.has_cap("csr") {
+            self.push_cap2("csr", top, bottom);
+            self.pos = None;
         }
     }
 
-    pub fn bg_color (&mut self, color: info::Color) {
-        match self.state.bg {
-            Some(c) if c == color => {}
-            _                     => {
-                self.state.bg = Some(color);
-                self.buf.push_str(info::set_a_background(color).as_slice());
-            }
+    pub fn reset_scroll_region (&mut self) {
+        if self.region.is_none() {
+            return;
+        }
+        self.region = None;
+        if self.has_cap("csr") && self.rows > 0 {
+            let rows = self.rows;
+            self.push_cap2("csr", 0, rows - 1);
+            self.pos = None;
         }
     }
 
-    pub fn underline (&mut self, enabled: bool) {
-        if self.state.underline != enabled {
-            self.state.underline = enabled;
-            if enabled {
-                self.buf.push_str(info::enter_underline_mode().as_slice());
-            }
-            else {
-                self.buf.push_str(info::exit_underline_mode().as_slice());
+    // the scroll region, when the terminal can't set it itself
+    fn emulated_region (&self) -> Option<(uint, uint)> {
+        if self.has_cap("csr") { None } else { self.region }
+    }
+
+    // runs `f` with the scroll region temporarily set to the rows from `top`
+    // to `bottom`, and puts the cursor back afterwards. the whole screen is
+    // the region if the terminal can't set it.
+    fn in_region (&mut self, top: uint, bottom: uint, f: |&mut TermWriter|) {
+        let pos = self.pos;
+        let region = self.region;
+        let csr = self.has_cap("csr");
+        if csr {
+            self.push_cap2("csr", top, bottom);
+            self.pos = None;
+        }
+        f(self);
+        let restore = match region {
+            Some(region)          => Some(region),
+            None if self.rows > 0 => Some((0, self.rows - 1)),
+            None                  => None,
+        };
+        match restore {
+            Some((top, bottom)) if csr => {
+                self.push_cap2("csr", top, bottom);
+                self.pos = None;
             }
+            _                          => (),
+        }
+        match pos {
+            Some((col, row)) => self.move_cursor(col, row),
+            None             => (),
         }
     }
 
-    pub fn standout (&mut self, enabled: bool) {
-        if self.state.standout != enabled {
-            self.state.standout = enabled;
-            if enabled {
-                self.buf.push_str(info::enter_standout_mode().as_slice());
-            }
-            else {
-                self.buf.push_str(info::exit_standout_mode().as_slice());
-            }
+    // whether writing to the last column of `row` makes the terminal scroll
+    fn scrolls_at (&self, row: uint) -> bool {
+        let wraps = self.info.auto_right_margin()
+            && !self.info.eat_newline_glitch();
+        // without csr, the terminal only knows about the whole screen
+        let bottom = match self.region {
+            Some((_, bottom)) if self.has_cap("csr") => Some(bottom),
+            _ if self.rows > 0                       => Some(self.rows - 1),
+            _                                        => None,
+        };
+        wraps && bottom == Some(row)
+    }
+
+    // the rows between the cursor and the bottom of the scroll region, if
+    // the cursor position is known
+    fn rows_below (&self) -> Option<(uint, uint)> {
+        let bottom = match self.region {
+            Some((_, bottom))           => bottom,
+            None if self.rows > 0       => self.rows - 1,
+            None                        => return None,
+        };
+        match self.pos {
+            Some((_, row)) if row <= bottom => Some((row, bottom)),
+            _                               => None,
         }
     }
 
-    pub fn reverse (&mut self, enabled: bool) {
-        if self.state.reverse != enabled {
-            self.state.reverse = enabled;
-            if enabled {
-                self.buf.push_str(info::enter_reverse_mode().as_slice());
+    pub fn insert_lines (&mut self, n: uint) {
+        if n == 0 {
+            return;
+        }
+        // il pushes lines off the bottom of the screen, so without csr the
+        // lines below the region have to be pulled back up first
+        match (self.emulated_region(), self.pos) {
+            (Some((top, bottom)), Some((col, row))) => {
+                if row < top || row > bottom {
+                    return;
+                }
+                let n = cmp::min(n, bottom + 1 - row);
+                if bottom + 1 < self.rows {
+                    self.move_cursor(0, bottom + 1 - n);
+                    self.pos = None;
+                    self.push_repeated("dl", "dl1", n);
+                    self.move_cursor(0, row);
+                }
+                self.pos = None;
+                self.push_repeated("il", "il1", n);
+                self.move_cursor(col, row);
+                return;
             }
-            else {
-                self.apply_state();
+            _                                       => (),
+        }
+        if self.has_cap("il") || self.has_cap("il1") {
+            self.pos = None;
+            self.push_repeated("il", "il1", n);
+            return;
+        }
+        // scrolling the part of the region below the cursor down does the
+        // same thing
+        match self.rows_below() {
+            Some((row, bottom)) if self.has_cap("csr") => {
+                self.in_region(row, bottom, |w| {
+                    w.move_cursor(0, row);
+                    w.scroll_reverse(n);
+                });
+            }
+            _                                          => {
+                self.push_repeated("il", "il1", n);
             }
         }
     }
 
-    pub fn bold (&mut self, enabled: bool) {
-        if self.state.bold != enabled {
-            self.state.bold = enabled;
-            if enabled {
-                self.buf.push_str(info::enter_bold_mode().as_slice());
+    pub fn delete_lines (&mut self, n: uint) {
+        if n == 0 {
+            return;
+        }
+        // and dl pulls them up, so they have to be pushed back down after
+        match (self.emulated_region(), self.pos) {
+            (Some((top, bottom)), Some((col, row))) => {
+                if row < top || row > bottom {
+                    return;
+                }
+                let n = cmp::min(n, bottom + 1 - row);
+                self.pos = None;
+                self.push_repeated("dl", "dl1", n);
+                if bottom + 1 < self.rows {
+                    self.move_cursor(0, bottom + 1 - n);
+                    self.pos = None;
+                    self.push_repeated("il", "il1", n);
+                }
+                self.move_cursor(col, row);
+                return;
             }
-            else {
-                self.apply_state();
+            _                                       => (),
+        }
+        if self.has_cap("dl") || self.has_cap("dl1") {
+            self.pos = None;
+            self.push_repeated("dl", "dl1", n);
+            return;
+        }
+        match self.rows_below() {
+            Some((row, bottom)) if self.has_cap("csr") => {
+                self.in_region(row, bottom, |w| {
+                    w.move_cursor(0, bottom);
+                    w.scroll_forward(n);
+                });
+            }
+            _                                          => {
+                self.push_repeated("dl", "dl1", n);
             }
         }
     }
 
-    pub fn blink (&mut self, enabled: bool) {
-        if self.state.blink != enabled {
-            self.state.blink = enabled;
-            if enabled {
-                self.buf.push_str(info::enter_blink_mode().as_slice());
+    pub fn insert_chars (&mut self, n: uint) {
+        if n == 0 {
+            return;
+        }
+        if self.has_cap("ich") || self.has_cap("ich1")
+            || !self.has_cap("smir")
+            || !(self.has_cap("cub") || self.has_cap("cub1")) {
+            self.push_repeated("ich", "ich1", n);
+            return;
+        }
+        // writing spaces in insert mode pushes the rest of the line right
+        self.push_cap("smir");
+        self.buf.push_str(" ".repeat(n).as_slice());
+        self.push_cap("rmir");
+        self.push_repeated("cub", "cub1", n);
+    }
+
+    pub fn delete_chars (&mut self, n: uint) {
+        self.push_repeated("dch", "dch1", n);
+    }
+
+    pub fn erase_chars (&mut self, n: uint) {
+        if n == 0 {
+            return;
+        }
+        if self.has_cap("ech") {
+            self.push_cap1("ech", n);
+            return;
+        }
+        // overwriting with spaces, without going past the end of the line
+        match self.pos {
+            Some((col, row)) => {
+                let mut n = if col < self.cols {
+                    cmp::min(n, self.cols - col)
+                }
+                else {
+                    n
+                };
+                // and without writing to the last cell at the bottom, on
+                // terminals where that scrolls
+                if col + n == self.cols && self.scrolls_at(row) {
+                    n -= 1;
+                }
+                self.buf.push_str(" ".repeat(n).as_slice());
+                self.pos = None;
+                self.move_cursor(col, row);
             }
-            else {
-                self.apply_state();
+            None             => {
+                self.buf.push_str(" ".repeat(n).as_slice());
+                self.push_repeated("cub", "cub1", n);
             }
         }
     }
 
+    pub fn fg_color (&mut self, color: Color) {
+        self.change(|attrs| { attrs.fg = color });
+    }
+
+    pub fn bg_color (&mut self, color: Color) {
+        self.change(|attrs| { attrs.bg = color });
+    }
+
+    pub fn color_mapping (&mut self, mapping: color::Mapping) {
+        self.palette.mapping = mapping;
+    }
+
+    fn push_color (&mut self, fg: bool, color: Color) {
+        match color_escape(&self.info, fg, color) {
+            Some(e) => self.buf.push_str(e.as_slice()),
+            None    => (),
+        }
+    }
+
+    pub fn underline (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.underline = enabled });
+    }
+
+    pub fn underline_style (&mut self, style: UnderlineStyle) {
+        self.change(|attrs| { attrs.underline_style = style });
+    }
+
+    pub fn underline_color (&mut self, color: Color) {
+        self.change(|attrs| { attrs.underline_color = color });
+    }
+
+    // the fancier styles fall back to a plain underline
+    fn push_underline (&mut self) {
+        let style = self.state.underline_style;
+        if style != SingleUnderline && self.has_cap("Smulx") {
+            self.push_cap1("Smulx", style as uint);
+        }
+        else {
+            self.push_cap("smul");
+        }
+    }
+
+    // Setulc takes the color as a single number, like 0xrrggbb. there is no
+    // capability for going back to the default, but everything that has
+    // Setulc understands the escape for it.
+    fn push_underline_color (&mut self) {
+        if !self.has_cap("Setulc") {
+            return;
+        }
+        match self.state.underline_color.to_rgb() {
+            Some((r, g, b)) => {
+                let rgb = ((r as uint) << 16) | ((g as uint) << 8) | b as uint;
+                self.push_cap1("Setulc", rgb);
+            }
+            None            => self.buf.push_str("\x1b[59m"),
+        }
+    }
+
+    pub fn standout (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.standout = enabled });
+    }
+
+    pub fn reverse (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.reverse = enabled });
+    }
+
+    pub fn bold (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.bold = enabled });
+    }
+
+    pub fn blink (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.blink = enabled });
+    }
+
+    pub fn italic (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.italic = enabled });
+    }
+
+    pub fn dim (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.dim = enabled });
+    }
+
+    pub fn invisible (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.invisible = enabled });
+    }
+
+    pub fn strikethrough (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.strikethrough = enabled });
+    }
+
+    pub fn overline (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.overline = enabled });
+    }
+
+    pub fn protected (&mut self, enabled: bool) {
+        self.change(|attrs| { attrs.protected = enabled });
+    }
+
     pub fn reset_color (&mut self) {
-        self.state.fg = None;
-        self.state.bg = None;
-        self.buf.push_str(info::orig_pair().as_slice());
+        self.change(|attrs| {
+            attrs.fg = DefaultColor;
+            attrs.bg = DefaultColor;
+        });
     }
 
     pub fn reset_attributes (&mut self) {
-        self.state = AttrState();
+        self.state = Attrs::new();
         self.apply_state();
     }
 
+    pub fn attrs (&self) -> Attrs {
+        self.state.clone()
+    }
+
+    fn change (&mut self, f: |&mut Attrs|) {
+        let mut attrs = self.state.clone();
+        f(&mut attrs);
+        self.set_attrs(&attrs);
+    }
+
+    pub fn set_attrs (&mut self, attrs: &Attrs) {
+        let mut attrs = attrs.clone();
+        // colors that the terminal can't show are replaced by the closest
+        // ones it has
+        attrs.fg = self.palette.approximate(attrs.fg);
+        attrs.bg = self.palette.approximate(attrs.bg);
+        attrs.underline_color = self.palette.approximate(attrs.underline_color);
+        if self.state == attrs {
+            return;
+        }
+
+        let old = self.visible_attrs();
+        self.state = attrs;
+        let new = self.visible_attrs();
+        if old == new {
+            return;
+        }
+
+        // most things can only be turned off by resetting everything, so
+        // only go through that when something actually needs to be
+        let start = self.buf.len();
+        if self.needs_reset(&old, &new) {
+            self.push_state();
+        }
+        else {
+            self.push_changes(&old, &new);
+        }
+        self.merge_sgr(start);
+    }
+
     pub fn apply_state (&mut self) {
-        self.buf.push_str(info::exit_attribute_mode().as_slice());
-        match self.state.fg {
-            Some(c) => self.fg_color(c),
-            None    => (),
+        let start = self.buf.len();
+        self.push_state();
+        self.merge_sgr(start);
+    }
+
+    // the attributes that are actually drawn. terminfo lists the ones that
+    // can't be combined with colors in ncv, so those are left out while
+    // colors are being used.
+    fn visible_attrs (&self) -> Attrs {
+        let mut attrs = self.state.clone();
+        if attrs.fg == DefaultColor && attrs.bg == DefaultColor {
+            return attrs;
         }
-        match self.state.bg {
-            Some(c) => self.bg_color(c),
-            None    => (),
+        let ncv = self.ncv;
+        attrs.standout = attrs.standout && ncv & 1 == 0;
+        attrs.underline = attrs.underline && ncv & 2 == 0;
+        attrs.reverse = attrs.reverse && ncv & 4 == 0;
+        attrs.blink = attrs.blink && ncv & 8 == 0;
+        attrs.dim = attrs.dim && ncv & 16 == 0;
+        attrs.bold = attrs.bold && ncv & 32 == 0;
+        attrs.invisible = attrs.invisible && ncv & 64 == 0;
+        attrs.protected = attrs.protected && ncv & 128 == 0;
+        attrs.italic = attrs.italic && ncv & 512 == 0;
+        attrs
+    }
+
+    fn needs_reset (&self, old: &Attrs, new: &Attrs) -> bool {
+        let colors_off = (old.fg != DefaultColor && new.fg == DefaultColor)
+            || (old.bg != DefaultColor && new.bg == DefaultColor);
+        if colors_off && !self.has_cap("op") {
+            return true;
+        }
+        if old.underline && !new.underline && !self.has_cap("rmul") {
+            return true;
+        }
+        let changes = toggles(old, new);
+        changes.iter().any(|&(was, is, _, exit)| {
+            was && !is && exit.map_or(true, |cap| { !self.has_cap(cap) })
+        })
+    }
+
+    fn push_changes (&mut self, old: &Attrs, new: &Attrs) {
+        // op resets both colors, so the other one has to be set again
+        if (old.fg != DefaultColor && new.fg == DefaultColor)
+            || (old.bg != DefaultColor && new.bg == DefaultColor) {
+            self.push_cap("op");
+            self.push_color(true, new.fg);
+            self.push_color(false, new.bg);
         }
-        if self.state.underline {
-            self.underline(true);
+        else {
+            if old.fg != new.fg {
+                self.push_color(true, new.fg);
+            }
+            if old.bg != new.bg {
+                self.push_color(false, new.bg);
+            }
         }
-        if self.state.standout {
-            self.standout(true);
+
+        if new.underline && (!old.underline
+                             || old.underline_style != new.underline_style) {
+            self.push_underline();
         }
-        if self.state.reverse {
-            self.reverse(true);
+        else if old.underline && !new.underline {
+            self.push_cap("rmul");
         }
-        if self.state.bold {
-            self.bold(true);
+        if old.underline_color != new.underline_color {
+            self.push_underline_color();
         }
-        if self.state.blink {
-            self.blink(true);
+
+        let changes = toggles(old, new);
+        for &(was, is, enter, exit) in changes.iter() {
+            if !was && is {
+                self.enter_mode(enter);
+            }
+            else if was && !is {
+                self.push_cap(exit.unwrap());
+            }
         }
     }
 
+    // emits the whole state from scratch. sgr can set most attributes in a
+    // single escape, and the rest go on top of that.
+    fn push_state (&mut self) {
+        let attrs = self.visible_attrs();
+        if self.has_cap("sgr") {
+            let params = [
+                attrs.standout as int, attrs.underline as int,
+                attrs.reverse as int, attrs.blink as int, attrs.dim as int,
+                attrs.bold as int, attrs.invisible as int,
+                attrs.protected as int, 0,
+            ];
+            let e = self.info.escapen("sgr", params);
+            self.push_escape(e);
+            // sgr can only draw a plain underline
+            if attrs.underline && attrs.underline_style != SingleUnderline {
+                self.push_underline();
+            }
+            self.replay_extras(&attrs);
+        }
+        else {
+            self.push_cap("sgr0");
+            self.replay_state();
+        }
+    }
+
+    // re-emits the escapes for the current state, assuming that the terminal
+    // has just been reset with sgr0
+    fn replay_state (&mut self) {
+        let attrs = self.visible_attrs();
+        if attrs.underline {
+            self.push_underline();
+        }
+        let changes = toggles(&Attrs::new(), &attrs);
+        for &(_, is, enter, _) in changes.iter() {
+            if is && sgr_mode(enter) {
+                self.enter_mode(enter);
+            }
+        }
+        self.replay_extras(&attrs);
+    }
+
+    // everything that sgr doesn't cover
+    fn replay_extras (&mut self, attrs: &Attrs) {
+        let changes = toggles(&Attrs::new(), attrs);
+        for &(_, is, enter, _) in changes.iter() {
+            if is && !sgr_mode(enter) {
+                self.enter_mode(enter);
+            }
+        }
+        self.replay_colors();
+        if attrs.underline_color != DefaultColor {
+            self.push_underline_color();
+        }
+    }
+
+    fn replay_colors (&mut self) {
+        let (fg, bg) = (self.state.fg, self.state.bg);
+        self.push_color(true, fg);
+        self.push_color(false, bg);
+    }
+
+    // joins the escapes written since `start`, so that the terminal gets the
+    // whole change at once
+    fn merge_sgr (&mut self, start: uint) {
+        let merged = join_sgr(self.buf.as_slice().slice_from(start));
+        self.buf.truncate(start);
+        self.buf.push_str(merged.as_slice());
+    }
+
     pub fn cursor (&mut self, enabled: bool) {
         if enabled {
-            self.buf.push_str(info::cursor_invisible().as_slice());
+            self.push_cap("cnorm");
         }
         else {
-            self.buf.push_str(info::cursor_normal().as_slice());
+            self.push_cap("civis");
         }
+        self.cursor_visible = enabled;
     }
 
     pub fn alternate_screen (&mut self, enabled: bool) {
+        self.pos = None;
+        self.alternate = enabled;
         if enabled {
-            self.buf.push_str(info::enter_ca_mode().as_slice());
+            self.push_cap("smcup");
         }
         else {
-            self.buf.push_str(info::exit_ca_mode().as_slice());
+            self.push_cap("rmcup");
         }
     }
 
+    pub fn mouse (&mut self, mode: Option<MouseMode>) {
+        // there are no terminfo capabilities for these, but everything that
+        // supports the mouse at all uses the xterm ones
+        match self.mouse {
+            Some(m) => {
+                self.buf.push_str(format!("\x1b[?{:u}l", m as uint).as_slice());
+            }
+            None    => (),
+        }
+        match mode {
+            Some(m) => {
+                self.buf.push_str(format!("\x1b[?{:u}h", m as uint).as_slice());
+                // ask for the sgr encoding, which works for large screens and
+                // reports which button was released
+                self.buf.push_str("\x1b[?1006h");
+            }
+            None    => self.buf.push_str("\x1b[?1006l"),
+        }
+        self.mouse = mode;
+    }
+
+    pub fn bracketed_paste (&mut self, enabled: bool) {
+        if enabled {
+            self.buf.push_str("\x1b[?2004h");
+        }
+        else {
+            self.buf.push_str("\x1b[?2004l");
+        }
+        self.paste = enabled;
+    }
+
+    fn modes (&self) -> Modes {
+        Modes {
+            attrs: self.state.clone(),
+            region: self.region,
+            mouse: self.mouse,
+            paste: self.paste,
+            alternate: self.alternate,
+            cursor_visible: self.cursor_visible,
+        }
+    }
+
+    // turns the modes from before a `restore` back on
+    fn set_modes (&mut self, modes: &Modes) {
+        if self.has_cap("smkx") {
+            self.push_cap("smkx");
+        }
+        if modes.alternate {
+            self.alternate_screen(true);
+        }
+        if !modes.cursor_visible {
+            self.cursor(false);
+        }
+        if modes.mouse.is_some() {
+            self.mouse(modes.mouse);
+        }
+        if modes.paste {
+            self.bracketed_paste(true);
+        }
+        match modes.region {
+            Some((top, bottom)) if bottom < self.rows => {
+                self.set_scroll_region(top, bottom);
+            }
+            _                                         => (),
+        }
+        self.set_attrs(&modes.attrs);
+    }
+
+    // what turns off everything that has been turned on, including the
+    // keypad mode from when we started
+    fn restore_sequence (&self) -> String {
+        let mut seq = String::new();
+        match self.mouse {
+            Some(m) => {
+                seq.push_str(format!("\x1b[?{:u}l", m as uint).as_slice());
+                seq.push_str("\x1b[?1006l");
+            }
+            None    => (),
+        }
+        if self.paste {
+            seq.push_str("\x1b[?2004l");
+        }
+        // setting the region moves the cursor, so that has to be undone
+        if self.region.is_some() && self.rows > 0 {
+            let caps = (
+                self.info.escape("sc"),
+                self.info.escape2("csr", 0, self.rows as int - 1),
+                self.info.escape("rc"),
+            );
+            match caps {
+                (Ok(Some(sc)), Ok(Some(csr)), Ok(Some(rc))) => {
+                    seq.push_str(sc.as_slice());
+                    seq.push_str(csr.as_slice());
+                    seq.push_str(rc.as_slice());
+                }
+                _                                           => (),
+            }
+        }
+        let caps = [
+            ("sgr0", true),
+            ("rmcup", self.alternate),
+            ("cnorm", !self.cursor_visible),
+            ("rmkx", true),
+        ];
+        for &(cap, needed) in caps.iter() {
+            match self.info.escape(cap) {
+                Ok(Some(ref e)) if needed => seq.push_str(e.as_slice()),
+                _                         => (),
+            }
+        }
+        seq
+    }
+
+    fn restore (&mut self) {
+        let seq = self.restore_sequence();
+        self.buf.push_str(seq.as_slice());
+        self.flush();
+        self.state = Attrs::new();
+        self.mouse = None;
+        self.paste = false;
+        self.alternate = false;
+        self.cursor_visible = true;
+        self.pos = None;
+        self.region = None;
+    }
+
     pub fn write (&mut self, text: &str) {
         self.buf.push_str(text);
+        self.pos = match self.pos {
+            Some((col, row)) => advance(col, row, text, self.cols),
+            None             => None,
+        };
     }
 
     pub fn flush (&mut self) {
@@ -419,102 +1717,920 @@ impl TermWriter {
         self.stream.flush();
         self.buf = "".to_string();
     }
+
+    fn has_cap (&self, name: &str) -> bool {
+        has_cap(&self.info, name)
+    }
+
+    fn push_clear (&mut self) {
+        if self.has_cap("clear") {
+            self.push_cap("clear");
+        }
+        else {
+            self.push_home();
+            self.push_cap("ed");
+        }
+    }
+
+    fn push_home (&mut self) {
+        if self.has_cap("home") {
+            self.push_cap("home");
+        }
+        else {
+            self.push_cap2("cup", 0, 0);
+        }
+    }
+
+    // uses the parameterized capability `name` if the terminal has it, and
+    // otherwise repeats `single` `n` times
+    fn push_repeated (&mut self, name: &str, single: &str, n: uint) {
+        if n == 0 {
+            return;
+        }
+        if self.has_cap(name) {
+            self.push_cap1(name, n);
+        }
+        else {
+            for _ in iter::range(0, n) {
+                self.push_cap(single);
+            }
+        }
+    }
+
+    // attributes the terminal doesn't have are just left out
+    fn enter_mode (&mut self, name: &str) {
+        if self.has_cap(name) {
+            self.push_cap(name);
+        }
+    }
+
+    fn push_cap (&mut self, name: &str) {
+        let e = self.info.escape(name);
+        self.push_escape(e);
+    }
+
+    fn push_cap1 (&mut self, name: &str, p1: uint) {
+        let e = self.info.escape1(name, p1 as int);
+        self.push_escape(e);
+    }
+
+    fn push_cap2 (&mut self, name: &str, p1: uint, p2: uint) {
+        let e = self.info.escape2(name, p1 as int, p2 as int);
+        self.push_escape(e);
+    }
+
+    // anything the terminal can't do is left out, rather than taking the
+    // whole program down with it. `support` is how callers can tell.
+    fn push_escape (&mut self, e: info::InfoResult<Option<String>>) {
+        match e {
+            Ok(Some(e)) => self.buf.push_str(e.as_slice()),
+            Ok(None)    => (),
+            Err(_)      => (),
+        }
+    }
+}
+
+// where the cursor ends up after writing `text` at (`col`, `row`), if that
+// can be known without knowing how the tty and terminal handle control
+// characters and the right margin
+fn advance (col: uint, row: uint, text: &str, cols: uint)
+            -> Option<(uint, uint)> {
+    let mut col = col;
+    for c in text.chars() {
+        if c == '\r' {
+            col = 0;
+        }
+        else if c < ' ' || c == '\x7f' {
+            return None;
+        }
+        else {
+            col += 1;
+            if col >= cols {
+                return None;
+            }
+        }
+    }
+    Some((col, row))
+}
+
+// the colors the terminal has. whether 24-bit colors can be used isn't
+// something terminfo has a standard way of saying, so that also looks at the
+// environment, and NO_COLOR turns colors off entirely.
+fn palette (info: &TermInfo) -> color::Palette {
+    match os::getenv("NO_COLOR") {
+        Some(ref v) if v.len() > 0 => return color::Palette::new(0, false),
+        _                          => (),
+    }
+    let truecolor = info.direct_color() || info.flag("Tc").unwrap_or(false)
+        || match os::getenv("COLORTERM") {
+            Some(ref v) => {
+                v.as_slice() == "truecolor" || v.as_slice() == "24bit"
+            }
+            None        => false,
+        };
+    color::Palette::new(info.max_colors().unwrap_or(0), truecolor)
+}
+
+// each attribute that is a simple on/off mode, before and after, with the
+// capabilities that turn it on and off. the ones without an exit capability
+// can only be turned off by resetting everything.
+fn toggles (old: &Attrs, new: &Attrs)
+            -> [(bool, bool, &'static str, Option<&'static str>), ..10] {
+    [
+        (old.standout, new.standout, "smso", Some("rmso")),
+        (old.reverse, new.reverse, "rev", None),
+        (old.bold, new.bold, "bold", None),
+        (old.blink, new.blink, "blink", None),
+        (old.dim, new.dim, "dim", None),
+        (old.invisible, new.invisible, "invis", None),
+        (old.protected, new.protected, "prot", None),
+        (old.italic, new.italic, "sitm", Some("ritm")),
+        (old.strikethrough, new.strikethrough, "smxx", Some("rmxx")),
+        (old.overline, new.overline, "Smol", Some("Rmol")),
+    ]
+}
+
+// whether sgr has a parameter for the mode that `cap` turns on
+fn sgr_mode (cap: &str) -> bool {
+    match cap {
+        "smso" | "rev" | "bold" | "blink" | "dim" | "invis" | "prot" => true,
+        _                                                          => false,
+    }
+}
+
+/**
+ * Joins escapes like `\e[1m\e[31m` that are right next to each other into a
+ * single `\e[1;31m`. Everything else in `s` is left alone.
+ */
+fn join_sgr (s: &str) -> String {
+    let mut out = String::new();
+    let mut params: Vec<&str> = vec!();
+    let mut rest = s;
+    while rest.len() > 0 {
+        match sgr_at(rest) {
+            Some((p, len)) => {
+                params.push(if p.len() == 0 { "0" } else { p });
+                rest = rest.slice_from(len);
+            }
+            None           => {
+                push_sgr(&mut out, &mut params);
+                let c = rest.char_at(0);
+                out.push_char(c);
+                rest = rest.slice_from(c.len_utf8_bytes());
+            }
+        }
+    }
+    push_sgr(&mut out, &mut params);
+    out
+}
+
+fn push_sgr (out: &mut String, params: &mut Vec<&str>) {
+    if params.len() > 0 {
+        out.push_str("\x1b[");
+        out.push_str(params.as_slice().connect(";").as_slice());
+        out.push_char('m');
+        params.clear();
+    }
+}
+
+// the parameters of the sgr escape at the start of `s`, if there is one, and
+// how long it is
+fn sgr_at<'a> (s: &'a str) -> Option<(&'a str, uint)> {
+    if !s.starts_with("\x1b[") {
+        return None;
+    }
+    let body = s.slice_from(2);
+    match body.find(|c: char| { !(c.is_digit() || c == ';' || c == ':') }) {
+        Some(i) if body.char_at(i) == 'm' => Some((body.slice_to(i), i + 3)),
+        _                                 => None,
+    }
+}
+
+// the escape which changes the foreground (or background) to `color`
+fn color_escape (info: &TermInfo, fg: bool, color: Color) -> Option<String> {
+    let (setaf, sgr) = if fg { ("setaf", 38u) } else { ("setab", 48u) };
+    match color {
+        DefaultColor      => None,
+        RgbColor(r, g, b) => {
+            let cap = if fg { "setrgbf" } else { "setrgbb" };
+            match info.escapen(cap, [r as int, g as int, b as int]) {
+                Ok(Some(e)) => Some(e),
+                _           => Some(format!("\x1b[{};2;{};{};{}m", sgr, r, g, b)),
+            }
+        }
+        _                 => {
+            let n = color.index().unwrap();
+            // direct color entries take rgb values in setaf, except for the
+            // first eight, so the rest of the palette needs its own escape
+            if n >= 8 && info.direct_color() {
+                Some(format!("\x1b[{};5;{}m", sgr, n))
+            }
+            else {
+                info.escape1(setaf, n as int).unwrap_or(None)
+            }
+        }
+    }
+}
+
+fn has_cap (info: &TermInfo, name: &str) -> bool {
+    match info.escape(name) {
+        Ok(Some(_)) => true,
+        _           => false,
+    }
+}
+
+fn support (info: &TermInfo, name: &str) -> Support {
+    if has_cap(info, name) {
+        return Native;
+    }
+    let any = |a: &str, b: &str| { has_cap(info, a) || has_cap(info, b) };
+    // these need to match the fallbacks in TermWriter
+    let emulated = match name {
+        "indn"                   => has_cap(info, "ind"),
+        "rin"                    => has_cap(info, "ri"),
+        "home"                   => has_cap(info, "cup"),
+        "clear"                  => has_cap(info, "ed")
+            && (has_cap(info, "home") || has_cap(info, "cup")),
+        "cup"                    => absolute(info, 1, 1).is_some(),
+        "rmul" | "rmso" | "op"   => has_cap(info, "sgr0"),
+        "ritm" | "rmxx" | "Rmol" => has_cap(info, "sgr0"),
+        "il" | "il1"             => any("il", "il1")
+            || (has_cap(info, "csr") && has_cap(info, "ri")),
+        "dl" | "dl1"             => any("dl", "dl1")
+            || (has_cap(info, "csr") && has_cap(info, "ind")),
+        "ich" | "ich1"           => any("ich", "ich1")
+            || (has_cap(info, "smir") && has_cap(info, "rmir")
+                && any("cub", "cub1")),
+        "dch" | "dch1"           => any("dch", "dch1"),
+        "ech"                    => any("cub", "cub1"),
+        _                        => false,
+    };
+    if emulated { Emulated } else { Unsupported }
+}
+
+/**
+ * The cheapest way to move the cursor from `from` (if it is known) to
+ * (`col`, `row`), like `mvcur` in curses.
+ *
+ * `text` is what is on the screen between the cursor and `col`, if the
+ * caller knows it, since writing it again is another way to move right.
+ * Returns `None` if the terminal has no way of getting there.
+ */
+fn motion (info: &TermInfo, from: Option<(uint, uint)>, col: uint, row: uint,
+           text: Option<&str>) -> Option<String> {
+    let mut candidates = vec!(absolute(info, col, row));
+    match from {
+        Some((c0, r0)) => {
+            let text = if r0 == row { text } else { None };
+            candidates.push(join(&[
+                vertical(info, r0, row, c0 == 0),
+                horizontal(info, c0, col, text),
+            ]));
+            candidates.push(join(&[
+                cap(info, "cr"),
+                vertical(info, r0, row, true),
+                horizontal(info, 0, col, None),
+            ]));
+            if row == r0 + 1 {
+                candidates.push(join(&[
+                    cap(info, "nel"),
+                    horizontal(info, 0, col, None),
+                ]));
+            }
+        }
+        None           => (),
+    }
+    cheapest(candidates)
+}
+
+// moves to (`col`, `row`) without knowing where the cursor is
+fn absolute (info: &TermInfo, col: uint, row: uint) -> Option<String> {
+    if col == 0 && row == 0 && has_cap(info, "home") {
+        return cap(info, "home");
+    }
+    match info.escape2("cup", row as int, col as int) {
+        Ok(Some(e)) => Some(e),
+        _           => join(&[
+            cap(info, "home"),
+            vertical(info, 0, row, true),
+            horizontal(info, 0, col, None),
+        ]),
+    }
+}
+
+fn vertical (info: &TermInfo, from: uint, to: uint, at_col0: bool)
+             -> Option<String> {
+    if from == to {
+        return Some(String::new());
+    }
+    let (n, single, multi) = if to > from {
+        (to - from, "cud1", "cud")
+    }
+    else {
+        (from - to, "cuu1", "cuu")
+    };
+
+    let mut candidates = vec!(cap1(info, multi, n), cap1(info, "vpa", to));
+    match cap(info, single) {
+        // the tty usually turns newlines into a carriage return and a
+        // newline, so they can only be used at the start of a line
+        Some(ref e) if e.as_slice() == "\n" && !at_col0 => (),
+        Some(e)                                         => {
+            candidates.push(Some(repeat(e.as_slice(), n)));
+        }
+        None                                            => (),
+    }
+    cheapest(candidates)
+}
+
+fn horizontal (info: &TermInfo, from: uint, to: uint, text: Option<&str>)
+               -> Option<String> {
+    if from == to {
+        return Some(String::new());
+    }
+    let (n, single, multi) = if to > from {
+        (to - from, "cuf1", "cuf")
+    }
+    else {
+        (from - to, "cub1", "cub")
+    };
+
+    let mut candidates = vec!(
+        cap1(info, multi, n),
+        cap1(info, "hpa", to),
+        cap(info, single).map(|e| { repeat(e.as_slice(), n) }),
+    );
+    match text {
+        Some(t) if to > from && t.char_len() == n
+                   && t.chars().all(|c| { c >= ' ' && c != '\x7f' }) => {
+            candidates.push(Some(t.to_string()));
+        }
+        _ => (),
+    }
+    cheapest(candidates)
+}
+
+fn cheapest (candidates: Vec<Option<String>>) -> Option<String> {
+    candidates.move_iter().filter_map(|c| { c }).min_by(|c| { c.len() })
+}
+
+// all of `parts` in a row, if they all exist
+fn join (parts: &[Option<String>]) -> Option<String> {
+    let mut s = String::new();
+    for part in parts.iter() {
+        match *part {
+            Some(ref p) => s.push_str(p.as_slice()),
+            None        => return None,
+        }
+    }
+    Some(s)
+}
+
+fn repeat (s: &str, n: uint) -> String {
+    let mut r = String::new();
+    for _ in iter::range(0, n) {
+        r.push_str(s);
+    }
+    r
+}
+
+fn cap (info: &TermInfo, name: &str) -> Option<String> {
+    match info.escape(name) {
+        Ok(e)  => e,
+        Err(_) => None,
+    }
+}
+
+fn cap1 (info: &TermInfo, name: &str, p1: uint) -> Option<String> {
+    match info.escape1(name, p1 as int) {
+        Ok(e)  => e,
+        Err(_) => None,
+    }
+}
+
+// how long to wait for the rest of an escape sequence, in microseconds
+static EscapeTimeout: int = 1000000;
+
+struct Timer {
+    id: uint,
+    // in util::now time
+    deadline: u64,
+    interval: Option<u64>,
+}
+
+// where a TermReader gets its input from
+enum Source {
+    // a descriptor, which is closed on drop if it's owned
+    FdSource(int, bool),
+    StreamSource(Box<Reader + Send>),
 }
 
 pub struct TermReader {
+    source: Source,
     escapes: Trie<Keypress>,
+    // characters which have been read, but not turned into keys yet
     buf: String,
+    // set when a resize happens in the middle of reading a key
+    resized: bool,
+    timers: Vec<Timer>,
+    next_timer: uint,
+    fds: Vec<int>,
+    // the read end of the pipe that Wakers write to
+    wakeup: int,
+    waker: Arc<WakeupPipe>,
 }
 
 impl TermReader {
-    fn new () -> TermReader {
-        TermReader { escapes: build_escapes_trie(), buf: "".to_string() }
+    fn new (info: &TermInfo, source: Source) -> TermReader {
+        // without the pipe, reading still works, there just won't be any
+        // wakeups (poll skips negative descriptors)
+        let (wakeup, waker) = util::open_wakeup().unwrap_or((-1, -1));
+        TermReader {
+            source: source,
+            escapes: build_escapes_trie(info),
+            buf: "".to_string(),
+            resized: false,
+            timers: vec!(),
+            next_timer: 0,
+            fds: vec!(),
+            wakeup: wakeup,
+            waker: Arc::new(WakeupPipe { fd: waker }),
+        }
     }
 
-    pub fn read (&mut self) -> Option<Keypress> {
-        if self.buf.len() > 0 {
-            return Some(self.next_key());
+    pub fn read (&mut self) -> Option<Event> {
+        if self.resized {
+            self.resized = false;
+            return Some(self.resize_event());
         }
 
-        let first = util::timed_read(-1);
-        if first.is_none() {
-            return None;
+        let first = if self.buf.len() > 0 {
+            self.buf.shift_char().unwrap()
         }
+        else {
+            match self.wait() {
+                Some(e) => return Some(e),
+                None    => (),
+            }
+            match self.read_input(0, false) {
+                util::InputChar(c) => c,
+                util::InputResize  => return Some(self.resize_event()),
+                util::InputNone    => return None,
+            }
+        };
+
+        if first == '\x1b' {
+            match self.read_csi() {
+                Some(e) => return Some(e),
+                None    => (),
+            }
+        }
+
+        let code = self.read_escape(first);
+        if code == KeyEscape && self.buf.len() > 0 {
+            // an escape which is immediately followed by something that
+            // isn't part of a known sequence is how alt is sent
+            return match self.read() {
+                Some(KeyEvent(k)) => {
+                    let mods = Modifiers { alt: true, ..k.mods };
+                    Some(KeyEvent(Key { code: k.code, mods: mods }))
+                }
+                e                 => e,
+            };
+        }
+        Some(KeyEvent(Key { code: code, mods: Modifiers::none() }))
+    }
 
-        let mut buf = str::from_char(*first.get_ref());
+    pub fn add_timer (&mut self, ms: uint, repeat: bool) -> uint {
+        let id = self.next_timer;
+        self.next_timer += 1;
+        self.timers.push(Timer {
+            id: id,
+            deadline: util::now() + ms as u64,
+            interval: if repeat { Some(ms as u64) } else { None },
+        });
+        id
+    }
+
+    pub fn cancel_timer (&mut self, id: uint) {
+        self.timers.retain(|t| { t.id != id });
+    }
+
+    pub fn waker (&self) -> Waker {
+        Waker { pipe: self.waker.clone() }
+    }
+
+    pub fn watch_fd (&mut self, fd: int) {
+        if !self.fds.contains(&fd) {
+            self.fds.push(fd);
+        }
+    }
+
+    pub fn unwatch_fd (&mut self, fd: int) {
+        self.fds.retain(|&f| { f != fd });
+    }
+
+    // waits until the tty has input, returning any other events that happen
+    // before that
+    fn wait (&mut self) -> Option<Event> {
+        // signals are only sent for the controlling terminal, which is the
+        // one we open ourselves
+        let (tty, signals) = match self.source {
+            FdSource(fd, owned) => (fd, owned),
+            // streams can only be waited on by reading them
+            StreamSource(_)     => return None,
+        };
         loop {
-            if !self.escapes.has_prefix(buf.as_slice()) {
-                /* XXX i think this is a borrow check bug, should look into
-                 * it at some point */
-                //return match self.escapes.find(buf) {
-                //    &Some(k) => { Some(k) }
-                //    &None    => {
-                //        self.unget(buf);
-                //        self.read()
-                //    }
-                //}
-                {
-                    let k = self.escapes.find(buf.as_slice());
-                    if k.is_some() {
-                        return *k;
-                    }
+            let now = util::now();
+            match self.expire_timer(now) {
+                Some(id) => return Some(TimerEvent(id)),
+                None     => (),
+            }
+
+            let timeout = match self.timers.iter().map(|t| { t.deadline }).min() {
+                Some(deadline) => (deadline - now) as int,
+                None           => -1,
+            };
+            let fds = self.fds.as_slice();
+            match util::wait(tty, self.wakeup, signals, fds, timeout) {
+                Ok(util::WaitTty)     => return None,
+                Ok(util::WaitResize)  => return Some(self.resize_event()),
+                Ok(util::WaitWakeup)  => return Some(WakeupEvent),
+                Ok(util::WaitFd(fd))  => return Some(FdEvent(fd)),
+                Ok(util::WaitTimeout) => (),
+                // the suspending itself happens in Term::read, which has
+                // the writer too
+                Ok(util::WaitSuspend) => return Some(ResumeEvent),
+                // the tty then only gets polled, so unless it happens to
+                // have input, this is reported as the end of it
+                Err(_)                => return None,
+            }
+        }
+    }
+
+    // returns the id of a timer that has gone off, if any
+    fn expire_timer (&mut self, now: u64) -> Option<uint> {
+        let i = match self.timers.iter().position(|t| { t.deadline <= now }) {
+            Some(i) => i,
+            None    => return None,
+        };
+        let id = self.timers.get(i).id;
+        match self.timers.get(i).interval {
+            Some(interval) => {
+                // if we fell behind, skip the missed ticks instead of
+                // returning them all at once
+                let timer = self.timers.get_mut(i);
+                timer.deadline += interval;
+                if timer.deadline <= now {
+                    timer.deadline = now + interval;
                 }
-                self.unget(buf.as_slice());
-                return self.read();
             }
+            None           => { self.timers.remove(i); }
+        }
+        Some(id)
+    }
+
+    // finds the longest key in the escapes trie that the input starts with
+    fn read_escape (&mut self, first: char) -> Keypress {
+        let mut seq = str::from_char(first);
+        let mut found = (1u, None);
+        match *self.escapes.find(seq.as_slice()) {
+            Some(k) => found = (1u, Some(k)),
+            None    => (),
+        }
+
+        while self.escapes.has_prefix(seq.as_slice()) {
+            match self.next_char(EscapeTimeout) {
+                Some(c) => seq.push_char(c),
+                None    => break,
+            }
+            match *self.escapes.find(seq.as_slice()) {
+                Some(k) => found = (seq.as_slice().char_len(), Some(k)),
+                None    => (),
+            }
+        }
+
+        // whatever was read past the end of the key belongs to the next one
+        let (len, k) = found;
+        self.unget(seq.as_slice().slice_chars(len, seq.as_slice().char_len()));
+        match k {
+            Some(k) => k,
+            None    => KeyCharacter(first),
+        }
+    }
+
+    // handles the CSI sequences which terminfo doesn't know about: keys
+    // pressed with modifiers, which xterm sends with an extra parameter,
+    // mouse events, and pastes. the escape has already been read.
+    fn read_csi (&mut self) -> Option<Event> {
+        match self.next_char(EscapeTimeout) {
+            Some('[') => (),
+            Some(c)   => {
+                self.unget(str::from_char(c).as_slice());
+                return None;
+            }
+            // nothing else is coming, so it was just the escape key
+            None      => {
+                let k = Key { code: KeyEscape, mods: Modifiers::none() };
+                return Some(KeyEvent(k));
+            }
+        }
 
-            match util::timed_read(1000000) {
-                Some(next) => { buf.push_char(next) }
-                None       => {
-                    self.unget(buf.as_slice());
-                    return self.read();
+        let mut seq = "[".to_string();
+        loop {
+            match self.next_char(EscapeTimeout) {
+                Some(c) => {
+                    seq.push_char(c);
+                    // the x10 mouse encoding is followed by three bytes
+                    // which can be anything, and which aren't utf-8
+                    if seq.as_slice() == "[M" {
+                        for _ in iter::range(0u, 3) {
+                            match self.next_byte(EscapeTimeout) {
+                                Some(c) => seq.push_char(c),
+                                None    => break,
+                            }
+                        }
+                        break;
+                    }
+                    if !(c.is_digit() || c == ';' || c == '<') {
+                        break;
+                    }
                 }
+                None    => break,
             }
         }
+
+        if seq.as_slice() == "[200~" {
+            return Some(PasteEvent(self.read_paste()));
+        }
+
+        let e = match decode_mouse(seq.as_slice()) {
+            Some(m) => Some(MouseEvent(m)),
+            None    => decode_modified(seq.as_slice()).map(|k| { KeyEvent(k) }),
+        };
+        if e.is_none() {
+            self.unget(seq.as_slice());
+        }
+        e
     }
 
+    // reads everything up to the end of a bracketed paste, without looking
+    // for keys in it
+    fn read_paste (&mut self) -> String {
+        let end = "\x1b[201~";
+        let mut text = String::new();
+        loop {
+            match self.next_char(-1) {
+                Some(c) => text.push_char(c),
+                None    => break,
+            }
+            if text.as_slice().ends_with(end) {
+                let len = text.len() - end.len();
+                text.truncate(len);
+                break;
+            }
+        }
+        text
+    }
+
+    fn next_char (&mut self, timeout: int) -> Option<char> {
+        self.next_input(timeout, false)
+    }
+
+    // reads a raw byte as a char, so bytes past 127 stay as they are
+    fn next_byte (&mut self, timeout: int) -> Option<char> {
+        self.next_input(timeout, true)
+    }
+
+    fn next_input (&mut self, timeout: int, raw: bool) -> Option<char> {
+        if self.buf.len() > 0 {
+            return self.buf.shift_char();
+        }
+        loop {
+            match self.read_input(timeout, raw) {
+                util::InputChar(c) => return Some(c),
+                util::InputNone    => return None,
+                // the rest of the key is still coming, so report the resize
+                // after it
+                util::InputResize  => self.resized = true,
+            }
+        }
+    }
+
+    // puts characters back in front of the ones that haven't been read yet
     fn unget (&mut self, buf: &str) {
-        self.buf.push_str(buf);
+        let rest = mem::replace(&mut self.buf, buf.to_string());
+        self.buf.push_str(rest.as_slice());
     }
 
-    fn next_key (&mut self) -> Keypress {
-        assert!(self.buf.len() > 0);
-        for i in iter::range(0, self.buf.len()) {
-            match self.escapes.find(self.buf.as_slice().slice(0, i)) {
-                &Some(k) => {
-                    for _ in iter::range(0, i) {
-                        self.buf.shift_char();
-                    }
-                    return k
+    fn read_input (&mut self, timeout: int, raw: bool) -> util::Input {
+        match self.source {
+            // only the controlling terminal (which is the one we open
+            // ourselves) gets resize signals
+            FdSource(fd, owned)     => {
+                if raw {
+                    util::timed_read_byte(fd, timeout, owned)
+                }
+                else {
+                    util::timed_read(fd, timeout, owned)
                 }
-                &None    => { }
             }
+            StreamSource(ref mut r) => {
+                if raw {
+                    util::stream_read_byte(&mut **r)
+                }
+                else {
+                    util::stream_read(&mut **r)
+                }
+            }
+        }
+    }
+
+    fn fd (&self) -> Option<int> {
+        match self.source {
+            FdSource(fd, _) => Some(fd),
+            StreamSource(_) => None,
         }
-        let next = self.buf.shift_char().unwrap();
-        return KeyCharacter(next);
+    }
+
+    // the descriptor, if it's for the controlling terminal
+    fn tty_fd (&self) -> Option<int> {
+        match self.source {
+            FdSource(fd, true) => Some(fd),
+            _                  => None,
+        }
+    }
+
+    fn resize_event (&self) -> Event {
+        let (cols, rows) = match self.source {
+            FdSource(fd, _) => ios::size(fd),
+            StreamSource(_) => (0, 0),
+        };
+        ResizeEvent(cols, rows)
     }
 }
 
-// XXX this whole thing needs to be able to deal with caps that don't exist
-fn build_escapes_trie () -> Trie<Keypress> {
+impl Drop for TermReader {
+    fn drop (&mut self) {
+        match self.source {
+            FdSource(fd, true) => util::close(fd),
+            _                  => (),
+        }
+        if self.wakeup >= 0 {
+            util::close(self.wakeup);
+        }
+    }
+}
+
+/**
+ * Decodes a mouse report, where `seq` is everything after the escape.
+ *
+ * These come in the x10 form `M<b><x><y>`, where each value is a raw byte
+ * offset by 32, the urxvt form `<b>;<x>;<y>M` with the same offset on `b`,
+ * or the sgr form `<<b>;<x>;<y>M`, with `m` at the end instead on release.
+ */
+fn decode_mouse (seq: &str) -> Option<Mouse> {
+    if seq.len() < 2 || !seq.starts_with("[") {
+        return None;
+    }
+
+    if seq.starts_with("[M") {
+        let chars: Vec<uint> = seq.slice_from(2).chars().map(|c| {
+            c as uint
+        }).collect();
+        return match chars.as_slice() {
+            [b, x, y] if b >= 32 && x > 32 && y > 32 => {
+                mouse_event(b - 32, x - 33, y - 33, false)
+            }
+            _                                        => None,
+        };
+    }
+
+    let last = seq.char_at(seq.len() - 1);
+    let (sgr, params) = if seq.starts_with("[<") {
+        (true, seq.slice(2, seq.len() - 1))
+    }
+    else {
+        (false, seq.slice(1, seq.len() - 1))
+    };
+    let params: Vec<Option<uint>> = params.split(';').map(|p| {
+        from_str::<uint>(p)
+    }).collect();
+    match (sgr, last, params.as_slice()) {
+        (true, 'M', [Some(b), Some(x), Some(y)]) if x > 0 && y > 0 => {
+            mouse_event(b, x - 1, y - 1, false)
+        }
+        (true, 'm', [Some(b), Some(x), Some(y)]) if x > 0 && y > 0 => {
+            mouse_event(b, x - 1, y - 1, true)
+        }
+        (false, 'M', [Some(b), Some(x), Some(y)])
+            if b >= 32 && x > 0 && y > 0 => {
+            mouse_event(b - 32, x - 1, y - 1, false)
+        }
+        _ => None,
+    }
+}
+
+// turns the button byte of a mouse report into an event. only the sgr form
+// says which button was released, the others report button 3 instead.
+fn mouse_event (b: uint, col: uint, row: uint, release: bool)
+                -> Option<Mouse> {
+    let mods = Modifiers {
+        shift: b & 4 != 0,
+        alt: b & 8 != 0,
+        ctrl: b & 16 != 0,
+        meta: false,
+    };
+    let motion = b & 32 != 0;
+    let button = match b & !(4 | 8 | 16 | 32) {
+        0  => MouseLeft,
+        1  => MouseMiddle,
+        2  => MouseRight,
+        3  => MouseNoButton,
+        64 => MouseWheelUp,
+        65 => MouseWheelDown,
+        66 => MouseWheelLeft,
+        67 => MouseWheelRight,
+        _  => return None,
+    };
+    let action = if motion {
+        if button == MouseNoButton { MouseMotion } else { MouseDrag }
+    }
+    else if release || button == MouseNoButton {
+        MouseRelease
+    }
+    else {
+        MousePress
+    };
+    Some(Mouse {
+        button: button,
+        action: action,
+        col: col,
+        row: row,
+        mods: mods,
+    })
+}
+
+// decodes CSI sequences of the form `1;<mod>X` and `<n>;<mod>~`, where `seq`
+// is everything after the escape
+fn decode_modified (seq: &str) -> Option<Key> {
+    if seq.len() < 2 || !seq.starts_with("[") {
+        return None;
+    }
+    let last = seq.char_at(seq.len() - 1);
+    let params: Vec<Option<uint>> = seq.slice(1, seq.len() - 1).split(';')
+        .map(|p| { from_str::<uint>(p) })
+        .collect();
+    let (n, m) = match params.as_slice() {
+        [Some(n), Some(m)] if m > 0 => (n, m),
+        _                           => return None,
+    };
+
+    let code = match (last, n) {
+        ('A', 1)         => KeyUp,
+        ('B', 1)         => KeyDown,
+        ('C', 1)         => KeyRight,
+        ('D', 1)         => KeyLeft,
+        ('H', 1)         => KeyHome,
+        ('F', 1)         => KeyEnd,
+        ('P', 1)         => KeyF(1),
+        ('Q', 1)         => KeyF(2),
+        ('R', 1)         => KeyF(3),
+        ('S', 1)         => KeyF(4),
+        ('~', 1)         => KeyHome,
+        ('~', 2)         => KeyInsert,
+        ('~', 3)         => KeyDelete,
+        ('~', 4)         => KeyEnd,
+        ('~', 7)         => KeyHome,
+        ('~', 8)         => KeyEnd,
+        ('~', 11..15)    => KeyF(n as int - 10),
+        ('~', 17..21)    => KeyF(n as int - 11),
+        ('~', 23..24)    => KeyF(n as int - 12),
+        _                => return None,
+    };
+    Some(Key { code: code, mods: Modifiers::from_param(m) })
+}
+
+fn build_escapes_trie (info: &TermInfo) -> Trie<Keypress> {
     let mut trie = Trie();
 
-    trie.insert(info::key_backspace().as_slice(), KeyBackspace);
+    insert_key(&mut trie, info, "kbs", KeyBackspace);
     trie.insert("\n",  KeyReturn);
     trie.insert("\t",  KeyTab);
 
-    trie.insert(info::key_up().as_slice(), KeyUp);
-    trie.insert(info::key_down().as_slice(), KeyDown);
-    trie.insert(info::key_left().as_slice(), KeyLeft);
-    trie.insert(info::key_right().as_slice(), KeyRight);
+    insert_key(&mut trie, info, "kcuu1", KeyUp);
+    insert_key(&mut trie, info, "kcud1", KeyDown);
+    insert_key(&mut trie, info, "kcub1", KeyLeft);
+    insert_key(&mut trie, info, "kcuf1", KeyRight);
 
-    trie.insert(info::key_home().as_slice(), KeyHome);
-    trie.insert(info::key_end().as_slice(),  KeyEnd);
-    trie.insert(info::key_ic().as_slice(), KeyInsert);
-    trie.insert(info::key_dc().as_slice(), KeyDelete);
+    insert_key(&mut trie, info, "khome", KeyHome);
+    insert_key(&mut trie, info, "kend",  KeyEnd);
+    insert_key(&mut trie, info, "kich1", KeyInsert);
+    insert_key(&mut trie, info, "kdch1", KeyDelete);
 
     for i in iter::range(1u, 12u) {
-        trie.insert(info::key_f(i).as_slice(), KeyF(i as int));
+        insert_key(&mut trie, info, format!("kf{:u}", i).as_slice(),
+                   KeyF(i as int));
     }
 
     for i in iter::range(1u8, 26u8) {
@@ -528,3 +2644,581 @@ fn build_escapes_trie () -> Trie<Keypress> {
 
     trie
 }
+
+// keys that the terminal doesn't have just won't be recognized
+fn insert_key (trie: &mut Trie<Keypress>, info: &TermInfo, name: &str,
+               k: Keypress) {
+    match info.escape(name) {
+        Ok(Some(e)) => trie.insert(e.as_slice(), k),
+        _           => (),
+    }
+}
+
+#[test]
+fn test_support () {
+    let linux = TermInfo::from_bytes(LinuxEntry.as_slice()).unwrap();
+    assert!(support(&linux, "ind") == Native);
+    assert!(support(&linux, "indn") == Emulated);
+    assert!(support(&linux, "rin") == Emulated);
+    assert!(support(&linux, "smcup") == Unsupported);
+    assert!(support(&linux, "notacap") == Unsupported);
+}
+
+#[test]
+fn test_decode_mouse () {
+    let mouse = |button, action, col, row| {
+        Some(Mouse {
+            button: button,
+            action: action,
+            col: col,
+            row: row,
+            mods: Modifiers::none(),
+        })
+    };
+
+    // x10, including a coordinate past 95, which is sent as a byte above 127
+    assert!(decode_mouse("[M !!") == mouse(MouseLeft, MousePress, 0, 0));
+    assert!(decode_mouse("[M#+%") == mouse(MouseNoButton, MouseRelease, 10, 4));
+    assert!(decode_mouse("[M`\u00c8!")
+            == mouse(MouseWheelUp, MousePress, 167, 0));
+
+    // urxvt
+    assert!(decode_mouse("[34;200;50M")
+            == mouse(MouseRight, MousePress, 199, 49));
+
+    // sgr
+    assert!(decode_mouse("[<0;5;7M") == mouse(MouseLeft, MousePress, 4, 6));
+    assert!(decode_mouse("[<1;5;7m") == mouse(MouseMiddle, MouseRelease, 4, 6));
+    assert!(decode_mouse("[<32;300;2M") == mouse(MouseLeft, MouseDrag, 299, 1));
+    assert!(decode_mouse("[<35;1;1M")
+            == mouse(MouseNoButton, MouseMotion, 0, 0));
+    assert!(decode_mouse("[<65;1;1M")
+            == mouse(MouseWheelDown, MousePress, 0, 0));
+
+    let ctrl_click = decode_mouse("[<16;3;3M").unwrap();
+    assert!(ctrl_click.mods == Modifiers { ctrl: true, ..Modifiers::none() });
+
+    assert!(decode_mouse("[1;5A").is_none());
+    assert!(decode_mouse("[<0;0;1M").is_none());
+    assert!(decode_mouse("[<0;1M").is_none());
+    assert!(decode_mouse("[M !").is_none());
+}
+
+#[test]
+fn test_decode_modified () {
+    let key = |code, mods| { Some(Key { code: code, mods: mods }) };
+    let shift = Modifiers { shift: true, ..Modifiers::none() };
+    let ctrl = Modifiers { ctrl: true, ..Modifiers::none() };
+    let ctrl_alt = Modifiers { ctrl: true, alt: true, ..Modifiers::none() };
+
+    assert!(decode_modified("[1;2A") == key(KeyUp, shift));
+    assert!(decode_modified("[1;5C") == key(KeyRight, ctrl));
+    assert!(decode_modified("[1;7H") == key(KeyHome, ctrl_alt));
+    assert!(decode_modified("[1;5P") == key(KeyF(1), ctrl));
+    assert!(decode_modified("[15;5~") == key(KeyF(5), ctrl));
+    assert!(decode_modified("[24;2~") == key(KeyF(12), shift));
+    assert!(decode_modified("[3;2~") == key(KeyDelete, shift));
+
+    assert!(decode_modified("[A").is_none());
+    assert!(decode_modified("[3~").is_none());
+    assert!(decode_modified("[1;0A").is_none());
+    assert!(decode_modified("[1;5Z").is_none());
+    assert!(decode_modified("[16;5~").is_none());
+}
+
+#[test]
+fn test_motion () {
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let m = |from: Option<(uint, uint)>, col: uint, row: uint,
+             text: Option<&str>| {
+        motion(&xterm, from, col, row, text)
+    };
+
+    assert!(m(None, 0, 0, None) == xterm.escape("home").unwrap());
+    assert!(m(None, 9, 4, None) == Some("\x1b[5;10H".to_string()));
+    assert!(m(Some((10, 4)), 9, 4, None) == Some("\x08".to_string()));
+    assert!(m(Some((10, 4)), 0, 4, None) == Some("\r".to_string()));
+    assert!(m(Some((10, 4)), 12, 4, Some("ab")) == Some("ab".to_string()));
+    assert!(m(Some((10, 4)), 11, 4, None) == Some("\x1b[C".to_string()));
+    assert!(m(Some((10, 4)), 10, 3, None) == Some("\x1b[A".to_string()));
+    // cud1 is a newline, which can't be trusted to keep the column
+    assert!(m(Some((10, 4)), 10, 5, None) != Some("\n".to_string()));
+    assert!(m(Some((0, 4)), 0, 5, None) == Some("\n".to_string()));
+
+    // without cup or home, there is nowhere to move from an unknown position
+    let dumb = TermInfo::from_bytes(DumbEntry.as_slice()).unwrap();
+    assert!(motion(&dumb, None, 3, 2, None) == None);
+}
+
+#[test]
+fn test_advance () {
+    assert!(advance(3, 2, "abc", 80) == Some((6, 2)));
+    assert!(advance(3, 2, "abc\rd", 80) == Some((1, 2)));
+    assert!(advance(3, 2, "abc\n", 80) == None);
+    assert!(advance(78, 2, "ab", 80) == None);
+}
+
+#[test]
+fn test_streams () {
+    use std::io::{ChanWriter, MemReader};
+    use vt::Vt;
+
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let input = Vec::from_slice("q\x1bOA".as_bytes());
+    let (tx, rx) = channel();
+    let mut term = Term::with_streams(
+        xterm,
+        box MemReader::new(input) as Box<Reader + Send>,
+        box ChanWriter::new(tx) as Box<Writer + Send>
+    ).unwrap();
+    term.resize(20, 5);
+
+    let key = |code| {
+        Some(KeyEvent(Key { code: code, mods: Modifiers::none() }))
+    };
+    assert!(term.read() == key(KeyCharacter('q')));
+    assert!(term.read() == key(KeyUp));
+    assert!(term.read() == None);
+
+    term.clear();
+    term.move_cursor(3, 2);
+    term.bold(true);
+    term.write("@");
+    term.flush();
+
+    let mut vt = Vt::new(20, 5);
+    feed_received(&mut vt, &rx);
+    assert!(vt.cell(3, 2).ch == '@');
+    assert!(vt.cell(3, 2).attrs.bold);
+    assert!(vt.cursor() == (4, 2));
+}
+
+#[test]
+fn test_read_x10_mouse () {
+    use std::io::{MemReader, MemWriter};
+
+    // a column past 95 is sent as a byte which isn't valid utf-8 on its own
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let input = vec!(0x1bu8, 0x5b, 0x4d, 0x20, 0xc3, 0x21, 0x71);
+    let mut term = Term::with_streams(
+        xterm,
+        box MemReader::new(input) as Box<Reader + Send>,
+        box MemWriter::new() as Box<Writer + Send>
+    ).unwrap();
+
+    let mouse = Mouse {
+        button: MouseLeft,
+        action: MousePress,
+        col: 162,
+        row: 0,
+        mods: Modifiers::none(),
+    };
+    assert!(term.read() == Some(MouseEvent(mouse)));
+    let key = Key { code: KeyCharacter('q'), mods: Modifiers::none() };
+    assert!(term.read() == Some(KeyEvent(key)));
+}
+
+#[test]
+fn test_read_paste () {
+    use std::io::{ChanReader, MemWriter};
+
+    // the paste ends up split across reads, and has an escape in it that
+    // would otherwise be read as the up key
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let (tx, rx) = channel();
+    tx.send(Vec::from_slice("\x1b[200~one\x1bOA".as_bytes()));
+    tx.send(Vec::from_slice("two\x1b[20".as_bytes()));
+    tx.send(Vec::from_slice("1~q".as_bytes()));
+    drop(tx);
+    let mut term = Term::with_streams(
+        xterm,
+        box ChanReader::new(rx) as Box<Reader + Send>,
+        box MemWriter::new() as Box<Writer + Send>
+    ).unwrap();
+
+    assert!(term.read() == Some(PasteEvent("one\x1bOAtwo".to_string())));
+    let key = Key { code: KeyCharacter('q'), mods: Modifiers::none() };
+    assert!(term.read() == Some(KeyEvent(key)));
+    assert!(term.read() == None);
+}
+
+#[test]
+fn test_timers () {
+    use std::io::MemReader;
+
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let input = box MemReader::new(vec!()) as Box<Reader + Send>;
+    let mut r = TermReader::new(&xterm, StreamSource(input));
+    let once = r.add_timer(100, false);
+    let tick = r.add_timer(30, true);
+    // start the clock at 0, so that the times below are exact
+    for t in r.timers.mut_iter() {
+        t.deadline = t.interval.unwrap_or(100);
+    }
+
+    assert!(r.expire_timer(10) == None);
+    assert!(r.expire_timer(30) == Some(tick));
+    assert!(r.expire_timer(30) == None);
+    assert!(r.expire_timer(60) == Some(tick));
+
+    // falling behind skips the missed ticks
+    assert!(r.expire_timer(200) == Some(once));
+    assert!(r.expire_timer(200) == Some(tick));
+    assert!(r.expire_timer(200) == None);
+    assert!(r.expire_timer(230) == Some(tick));
+
+    r.cancel_timer(tick);
+    assert!(r.expire_timer(1000) == None);
+    assert!(r.timers.len() == 0);
+}
+
+#[test]
+fn test_wakeup () {
+    let (master, slave) = ios::open_pty().unwrap();
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let mut r = TermReader::new(&xterm, FdSource(slave, false));
+    let other = TermReader::new(&xterm, FdSource(slave, false));
+
+    r.waker().wake();
+    assert!(r.read() == Some(WakeupEvent));
+
+    // each reader has its own pipe, so waking another one does nothing here
+    other.waker().wake();
+    let id = r.add_timer(10, false);
+    assert!(r.read() == Some(TimerEvent(id)));
+
+    util::close(master);
+    util::close(slave);
+}
+
+#[test]
+fn test_restore () {
+    use std::io::{ChanWriter, MemReader};
+    use color::AnsiColor;
+    use vt::Vt;
+
+    let xterm = TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let (tx, rx) = channel();
+    {
+        let mut term = Term::with_streams(
+            xterm,
+            box MemReader::new(vec!()) as Box<Reader + Send>,
+            box ChanWriter::new(tx) as Box<Writer + Send>
+        ).unwrap();
+        term.alternate_screen(true);
+        term.cursor(false);
+        term.bold(true);
+        term.fg_color(AnsiColor(info::ColorRed));
+        term.flush();
+    }
+
+    let mut vt = Vt::new(20, 5);
+    feed_received(&mut vt, &rx);
+    assert!(!vt.alternate_screen());
+    assert!(vt.cursor_visible());
+    assert!(vt.attrs() == &Attrs::new());
+}
+
+#[test]
+fn test_colors () {
+    use color::{AnsiColor, IndexedColor};
+
+    let mut w = test_writer(XtermEntry.as_slice(), 80, 24);
+    w.palette = color::Palette::new(8, false);
+    w.fg_color(RgbColor(250, 10, 20));
+    assert!(w.state.fg == AnsiColor(info::ColorRed));
+    w.palette = color::Palette::new(0, false);
+    w.buf = "".to_string();
+    w.bg_color(IndexedColor(200));
+    assert!(w.buf.as_slice() == "");
+    assert!(w.state.bg == DefaultColor);
+
+    let mut w = test_writer(XtermEntry.as_slice(), 80, 24);
+    w.palette = color::Palette::new(256, true);
+    w.fg_color(IndexedColor(200));
+    w.bg_color(RgbColor(1, 2, 3));
+    w.buf.push_str("a");
+    w.fg_color(DefaultColor);
+    w.buf.push_str("b");
+
+    let vt = render(&w, 4, 1);
+    assert!(vt.cell(0, 0).attrs.fg == IndexedColor(200));
+    assert!(vt.cell(0, 0).attrs.bg == RgbColor(1, 2, 3));
+    assert!(vt.cell(1, 0).attrs.fg == DefaultColor);
+    assert!(vt.cell(1, 0).attrs.bg == RgbColor(1, 2, 3));
+}
+
+#[test]
+fn test_extended_attrs () {
+    let mut w = test_writer(XtermEntry.as_slice(), 80, 24);
+    w.italic(true);
+    w.strikethrough(true);
+    w.buf.push_str("a");
+    w.italic(false);
+    w.dim(true);
+    w.buf.push_str("b");
+    w.dim(false);
+    w.buf.push_str("c");
+
+    let vt = render(&w, 4, 1);
+    let a = vt.cell(0, 0).attrs.clone();
+    assert!(a.italic && a.strikethrough && !a.dim);
+    let b = vt.cell(1, 0).attrs.clone();
+    assert!(!b.italic && b.strikethrough && b.dim);
+    let c = vt.cell(2, 0).attrs.clone();
+    assert!(!c.italic && c.strikethrough && !c.dim);
+    assert!(c == w.attrs());
+
+    // the linux console has none of these, so they're just tracked
+    let mut w = test_writer(LinuxEntry.as_slice(), 80, 24);
+    w.italic(true);
+    w.overline(true);
+    w.protected(true);
+    assert!(w.buf.as_slice() == "");
+    assert!(w.attrs().italic && w.attrs().overline && w.attrs().protected);
+}
+
+// compiled with tic -x from:
+//   rtest4|rust-term underline test terminal,
+//       am,
+//       colors#256, cols#80, lines#24,
+//       clear=\E[H\E[J, cup=\E[%i%p1%d;%p2%dH, rmul=\E[24m, sgr0=\E[m,
+//       smul=\E[4m, Smulx=\E[4:%p1%dm,
+#[cfg(test)]
+static UnderlineEntry: [u8, ..245] = [
+    0x1a, 0x01, 0x29, 0x00, 0x02, 0x00, 0x0e, 0x00, 0x2d, 0x00, 0x27, 0x00,
+    0x72, 0x74, 0x65, 0x73, 0x74, 0x34, 0x7c, 0x72, 0x75, 0x73, 0x74, 0x2d,
+    0x74, 0x65, 0x72, 0x6d, 0x20, 0x75, 0x6e, 0x64, 0x65, 0x72, 0x6c, 0x69,
+    0x6e, 0x65, 0x20, 0x74, 0x65, 0x73, 0x74, 0x20, 0x74, 0x65, 0x72, 0x6d,
+    0x69, 0x6e, 0x61, 0x6c, 0x00, 0x00, 0x01, 0x00, 0x50, 0x00, 0xff, 0xff,
+    0x18, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x01,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x07, 0x00, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x18, 0x00, 0xff, 0xff, 0xff, 0xff, 0x1d, 0x00, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0x21, 0x00, 0x1b, 0x5b, 0x48, 0x1b, 0x5b, 0x4a,
+    0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25,
+    0x70, 0x32, 0x25, 0x64, 0x48, 0x00, 0x1b, 0x5b, 0x34, 0x6d, 0x00, 0x1b,
+    0x5b, 0x6d, 0x00, 0x1b, 0x5b, 0x32, 0x34, 0x6d, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x1b, 0x5b, 0x34, 0x3a, 0x25, 0x70, 0x31, 0x25, 0x64, 0x6d, 0x00, 0x53,
+    0x6d, 0x75, 0x6c, 0x78, 0x00,
+];
+
+#[test]
+fn test_underline_styles () {
+    use std::io::MemWriter;
+
+    // like tmux, this has Smulx but not Setulc
+    let info = TermInfo::from_bytes(UnderlineEntry.as_slice()).unwrap();
+    let stream = box MemWriter::new() as Box<Writer + Send>;
+    let mut w = TermWriter::new(stream, info, 80, 24);
+    w.underline_style(CurlyUnderline);
+    w.underline(true);
+    w.underline_color(RgbColor(1, 2, 3));
+    w.buf.push_str("a");
+    w.underline_style(SingleUnderline);
+    w.buf.push_str("b");
+
+    let vt = render(&w, 4, 1);
+    let a = vt.cell(0, 0).attrs.clone();
+    assert!(a.underline && a.underline_style == CurlyUnderline);
+    assert!(a.underline_color == DefaultColor);
+    let b = vt.cell(1, 0).attrs.clone();
+    assert!(b.underline && b.underline_style == SingleUnderline);
+
+    let mut w = test_writer(XtermEntry.as_slice(), 80, 24);
+    w.underline_style(DottedUnderline);
+    w.underline(true);
+    w.buf.push_str("a");
+    let vt = render(&w, 4, 1);
+    let a = vt.cell(0, 0).attrs.clone();
+    assert!(a.underline && a.underline_style == SingleUnderline);
+    assert!(w.attrs().underline_style == DottedUnderline);
+}
+
+#[test]
+fn test_sgr () {
+    use color::AnsiColor;
+
+    let mut w = test_writer(XtermEntry.as_slice(), 80, 24);
+    let sgr = w.info.escapen("sgr", [0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+    w.bold(true);
+    w.reverse(true);
+    w.buf = "".to_string();
+    w.bold(false);
+    assert!(Some(w.buf.clone()) == sgr);
+
+    w.reset_attributes();
+    w.buf = "".to_string();
+    let mut attrs = Attrs::new();
+    attrs.fg = AnsiColor(info::ColorRed);
+    attrs.underline = true;
+    attrs.bold = true;
+    w.set_attrs(&attrs);
+    assert!(w.buf.as_slice() == "\x1b[31;4;1m");
+
+    // the linux console can't underline colored text
+    let mut w = test_writer(LinuxEntry.as_slice(), 80, 24);
+    w.underline(true);
+    w.buf.push_str("a");
+    w.fg_color(AnsiColor(info::ColorRed));
+    w.buf.push_str("b");
+    w.fg_color(DefaultColor);
+    w.buf.push_str("c");
+    assert!(w.attrs().underline);
+
+    let vt = render(&w, 4, 1);
+    assert!(vt.cell(0, 0).attrs.underline);
+    let b = vt.cell(1, 0).attrs.clone();
+    assert!(!b.underline && b.fg == AnsiColor(info::ColorRed));
+    let c = vt.cell(2, 0).attrs.clone();
+    assert!(c.underline && c.fg == DefaultColor);
+}
+
+#[test]
+fn test_join_sgr () {
+    assert!(join_sgr("\x1b[1m\x1b[mx\x1b[4:3m\x1b[31m\x1b(B").as_slice()
+            == "\x1b[1;0mx\x1b[4:3;31m\x1b(B");
+    assert!(join_sgr("\x1b[2J\x1b[1m").as_slice() == "\x1b[2J\x1b[1m");
+    assert!(join_sgr("").as_slice() == "");
+}
+
+// a writer for the compiled terminfo `entry`, which keeps what it writes in
+// `buf`
+#[cfg(test)]
+fn test_writer (entry: &[u8], cols: uint, rows: uint) -> TermWriter {
+    use std::io::MemWriter;
+
+    let info = TermInfo::from_bytes(entry).unwrap();
+    let stream = box MemWriter::new() as Box<Writer + Send>;
+    TermWriter::new(stream, info, cols, rows)
+}
+
+// what a terminal shows after everything `w` has written so far
+#[cfg(test)]
+fn render (w: &TermWriter, cols: uint, rows: uint) -> ::vt::Vt {
+    let mut vt = ::vt::Vt::new(cols, rows);
+    vt.feed(w.buf.as_slice());
+    vt
+}
+
+// feeds everything a ChanWriter has sent so far to `vt`
+#[cfg(test)]
+fn feed_received (vt: &mut ::vt::Vt, rx: &Receiver<Vec<u8>>) {
+    loop {
+        match rx.try_recv() {
+            Ok(bytes) => vt.feed(str::from_utf8(bytes.as_slice()).unwrap()),
+            Err(_)    => break,
+        }
+    }
+}
+
+#[cfg(test)]
+fn screen_text (vt: &::vt::Vt) -> String {
+    let (_, rows) = vt.size();
+    let lines: Vec<String> = range(0, rows).map(|row| {
+        vt.row_text(row)
+    }).collect();
+    lines.as_slice().connect(",")
+}
+
+#[test]
+fn test_scroll_region () {
+    let lines = "0\r\n1\r\n2\r\n3\r\n4";
+
+    let mut w = test_writer(XtermEntry.as_slice(), 10, 5);
+    // regions which aren't on the screen are ignored
+    w.set_scroll_region(3, 1);
+    w.set_scroll_region(1, 5);
+    assert!(w.region.is_none() && w.buf.as_slice() == "");
+
+    w.buf.push_str(lines);
+    w.set_scroll_region(1, 3);
+    w.move_cursor(0, 3);
+    w.scroll_forward(1);
+    let vt = render(&w, 10, 5);
+    assert!(screen_text(&vt).as_slice() == "0,2,3,,4");
+    assert!(vt.scroll_region() == (1, 3));
+
+    // leaving has to reset the region without moving the cursor
+    w.move_cursor(2, 2);
+    let seq = w.restore_sequence();
+    w.buf.push_str(seq.as_slice());
+    let vt = render(&w, 10, 5);
+    assert!(vt.scroll_region() == (0, 4));
+    assert!(vt.cursor() == (2, 2));
+
+    // without csr, scrolling the region deletes and inserts lines
+    let mut w = test_writer(AnsiEntry.as_slice(), 10, 5);
+    w.buf.push_str(lines);
+    w.set_scroll_region(1, 3);
+    w.move_cursor(0, 0);
+    w.scroll_forward(1);
+    let vt = render(&w, 10, 5);
+    assert!(screen_text(&vt).as_slice() == "0,2,3,,4");
+    assert!(vt.cursor() == (0, 0));
+
+    // and so are inserting and deleting lines, which mustn't move the rows
+    // below the region
+    w.move_cursor(0, 2);
+    w.insert_lines(1);
+    let vt = render(&w, 10, 5);
+    assert!(screen_text(&vt).as_slice() == "0,2,,3,4");
+    assert!(vt.cursor() == (0, 2));
+    w.delete_lines(2);
+    let vt = render(&w, 10, 5);
+    assert!(screen_text(&vt).as_slice() == "0,2,,,4");
+    assert!(vt.cursor() == (0, 2));
+    assert!(support(&w.info, "csr") == Unsupported);
+}
+
+#[test]
+fn test_edit_emulation () {
+    // vt100 doesn't have il or dl, but they can be done by scrolling
+    let mut w = test_writer(Vt100Entry.as_slice(), 10, 5);
+    assert!(support(&w.info, "il") == Emulated);
+    w.buf.push_str("0\r\n1\r\n2\r\n3\r\n4");
+    w.move_cursor(0, 1);
+    w.insert_lines(2);
+    let vt = render(&w, 10, 5);
+    assert!(screen_text(&vt).as_slice() == "0,,,1,2");
+    assert!(vt.scroll_region() == (0, 4));
+    assert!(vt.cursor() == (0, 1));
+
+    w.delete_lines(1);
+    let vt = render(&w, 10, 5);
+    assert!(screen_text(&vt).as_slice() == "0,,1,2,");
+
+    // xterm-mono has neither ich nor ech
+    let mut w = test_writer(XtermMonoEntry.as_slice(), 10, 5);
+    assert!(support(&w.info, "ich") == Emulated);
+    w.buf.push_str("abcdef");
+    w.move_cursor(1, 0);
+    w.insert_chars(2);
+    w.move_cursor(4, 0);
+    w.erase_chars(2);
+    let vt = render(&w, 10, 5);
+    assert!(vt.row_text(0).as_slice() == "a  b  ef");
+    assert!(vt.cursor() == (4, 0));
+
+    // pcansi has no ech either, and scrolls as soon as the last cell at the
+    // bottom is written, so that one is left alone
+    let mut w = test_writer(PcansiEntry.as_slice(), 10, 5);
+    w.move_cursor(7, 4);
+    w.buf = "".to_string();
+    w.erase_chars(5);
+    assert!(w.buf.as_slice().slice_to(3) == "  \x1b");
+
+    // and dumb can't do any of it, so nothing is written
+    let mut w = test_writer(DumbEntry.as_slice(), 10, 5);
+    assert!(support(&w.info, "il") == Unsupported);
+    w.insert_lines(2);
+    w.delete_chars(1);
+    w.bold(true);
+    assert!(w.buf.as_slice() == "");
+}
@@ -1,7 +1,18 @@
 use std::{fmt, os, str};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::posix::Path;
+
+pub use self::error::{Error, InfoResult, NoTerminal, UnknownTerminal,
+                      UnknownCapability, UnsupportedCapability, ParameterError,
+                      DatabaseError};
+
+mod compiled;
+mod error;
+mod params;
 
 /// The default colors available on a terminal emulator.
-#[deriving(PartialEq, Eq)]
+#[deriving(PartialEq, Eq, Clone, Show)]
 pub enum Color {
     ColorBlack = 0,
     ColorRed,
@@ -81,6 +92,8 @@ enum Capability {
 // XXX can't declare these as part of their enums, because of rust/#5873
 static NumTerms: uint = 12;
 static NumCapabilities: uint = 47;
+static NumNumbers: uint = 5;
+static NumFlags: uint = 5;
 
 // NOTE: generated by util/gen_builtin_db.pl
 static db: [[Option<&'static str>, ..NumCapabilities], ..NumTerms] = [
@@ -674,37 +687,493 @@ static db: [[Option<&'static str>, ..NumCapabilities], ..NumTerms] = [
     ],
 ];
 
-pub fn init () {
-    // nothing
-    // TODO cache the value of TERM here, so we don't have to keep looking it
-    // up? this will require mutable globals
+// NOTE: generated by util/gen_builtin_db.pl
+static number_db: [[Option<int>, ..NumNumbers], ..NumTerms] = [
+    [ // linux
+        None, // cols
+        None, // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // xterm
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // xterm-256color
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(256), // colors
+        Some(65536), // pairs
+    ],
+    [ // screen
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // screen-256color
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(256), // colors
+        Some(65536), // pairs
+    ],
+    [ // rxvt
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // rxvt-unicode
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(88), // colors
+        Some(7744), // pairs
+    ],
+    [ // rxvt-unicode-256color
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(256), // colors
+        Some(32767), // pairs
+    ],
+    [ // aterm
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // Eterm
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // kterm
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+    [ // gnome
+        Some(80), // cols
+        Some(24), // lines
+        Some(8), // it
+        Some(8), // colors
+        Some(64), // pairs
+    ],
+];
+
+// NOTE: generated by util/gen_builtin_db.pl
+static flag_db: [[bool, ..NumFlags], ..NumTerms] = [
+    [ // linux
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        false, // km
+    ],
+    [ // xterm
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // xterm-256color
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // screen
+        true, // am
+        true, // xenl
+        false, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // screen-256color
+        true, // am
+        true, // xenl
+        false, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // rxvt
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        false, // km
+    ],
+    [ // rxvt-unicode
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // rxvt-unicode-256color
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // aterm
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        false, // km
+    ],
+    [ // Eterm
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        false, // km
+    ],
+    [ // kterm
+        true, // am
+        true, // xenl
+        false, // bce
+        true, // msgr
+        true, // km
+    ],
+    [ // gnome
+        true, // am
+        true, // xenl
+        true, // bce
+        true, // msgr
+        false, // km
+    ],
+];
+
+// the names of the columns in number_db and flag_db
+static NumberNames: [&'static str, ..NumNumbers] = [
+    "cols", "lines", "it", "colors", "pairs",
+];
+static FlagNames: [&'static str, ..NumFlags] = [
+    "am", "xenl", "bce", "msgr", "km",
+];
+
+/// The capabilities of a particular type of terminal.
+pub struct TermInfo {
+    entry: compiled::Entry,
+    // the static variables used by parameterized capabilities are kept for
+    // as long as the terminal is, like they are in ncurses
+    vars: RefCell<params::Variables>,
 }
 
-pub fn escape (name: &str) -> Option<String> {
-    let cap = name_to_cap(name).expect(
-        format!("Unknown terminal capability {:s}", name).as_slice()
-    );
-    escape_cap(cap)
+impl TermInfo {
+    /**
+     * Loads the capabilities of the current terminal.
+     *
+     * The current terminal is determined by looking at the `TERM`
+     * environment variable.
+     */
+    pub fn from_env () -> InfoResult<TermInfo> {
+        match os::getenv("TERM") {
+            Some(termname) => TermInfo::from_name(termname.as_slice()),
+            None           => Err(NoTerminal),
+        }
+    }
+
+    /**
+     * Loads the capabilities of the terminal named `name`.
+     *
+     * Its compiled terminfo entry is searched for in the usual places, and
+     * if none is found, the small built-in table is used instead.
+     */
+    pub fn from_name (name: &str) -> InfoResult<TermInfo> {
+        match compiled::find(name) {
+            Some(path) => TermInfo::from_file(&path),
+            // the built-in table is only a last resort, for systems without
+            // a terminfo database
+            None       => match name_to_term(name) {
+                Some(term) => Ok(TermInfo::from_table(name, term)),
+                None       => Err(UnknownTerminal(name.to_string())),
+            },
+        }
+    }
+
+    /// Loads the capabilities from the compiled terminfo entry at `path`.
+    pub fn from_file (path: &Path) -> InfoResult<TermInfo> {
+        match compiled::load_file(path) {
+            Ok(entry) => Ok(TermInfo::from_entry(entry)),
+            Err(e)    => Err(DatabaseError(e)),
+        }
+    }
+
+    /// Loads the capabilities from the contents of a compiled terminfo entry.
+    pub fn from_bytes (bytes: &[u8]) -> InfoResult<TermInfo> {
+        match compiled::parse(bytes) {
+            Ok(entry) => Ok(TermInfo::from_entry(entry)),
+            Err(e)    => Err(DatabaseError(e)),
+        }
+    }
+
+    fn from_entry (entry: compiled::Entry) -> TermInfo {
+        TermInfo { entry: entry, vars: RefCell::new(params::Variables::new()) }
+    }
+
+    fn from_table (name: &str, term: Term) -> TermInfo {
+        let mut strings = HashMap::new();
+        for &cap_name in compiled::StrNames.iter() {
+            match name_to_cap(cap_name) {
+                Some(cap) => match escape_cap(term, cap) {
+                    Some(e) => { strings.insert(cap_name.to_string(), e); }
+                    None    => (),
+                },
+                None      => (),
+            }
+        }
+        let mut numbers = HashMap::new();
+        for (i, n) in number_db[term as uint].iter().enumerate() {
+            match *n {
+                Some(n) => { numbers.insert(NumberNames[i].to_string(), n); }
+                None    => (),
+            }
+        }
+        let mut bools = HashMap::new();
+        for (i, &b) in flag_db[term as uint].iter().enumerate() {
+            if b {
+                bools.insert(FlagNames[i].to_string(), true);
+            }
+        }
+        TermInfo::from_entry(compiled::Entry {
+            names: vec!(name.to_string()),
+            bools: bools,
+            numbers: numbers,
+            strings: strings,
+        })
+    }
+
+    /// The names of this terminal type.
+    pub fn names<'a> (&'a self) -> &'a [String] {
+        self.entry.names.as_slice()
+    }
+
+    /// The value of the boolean capability `name`.
+    pub fn flag (&self, name: &str) -> InfoResult<bool> {
+        if self.entry.bools.find_equiv(&name).is_some() {
+            Ok(true)
+        }
+        else if compiled::is_bool_cap(name) {
+            Ok(false)
+        }
+        else {
+            Err(UnknownCapability(name.to_string()))
+        }
+    }
+
+    /// The value of the numeric capability `name`, if the terminal has it.
+    pub fn number (&self, name: &str) -> InfoResult<Option<int>> {
+        match self.entry.numbers.find_equiv(&name) {
+            Some(n) => Ok(Some(*n)),
+            None if compiled::is_number_cap(name) => Ok(None),
+            None    => Err(UnknownCapability(name.to_string())),
+        }
+    }
+
+    // the typed accessors below only use names which are known to exist
+    fn known_number (&self, name: &str) -> Option<uint> {
+        self.number(name).unwrap().map(|n| { n as uint })
+    }
+
+    fn known_flag (&self, name: &str) -> bool {
+        self.flag(name).unwrap()
+    }
+
+    /// The number of columns on the terminal (`cols`).
+    pub fn columns (&self) -> Option<uint> {
+        self.known_number("cols")
+    }
+
+    /// The number of lines on the terminal (`lines`).
+    pub fn lines (&self) -> Option<uint> {
+        self.known_number("lines")
+    }
+
+    /// The initial spacing of tab stops (`it`).
+    pub fn init_tabs (&self) -> Option<uint> {
+        self.known_number("it")
+    }
+
+    /// The maximum number of colors the terminal can display (`colors`).
+    pub fn max_colors (&self) -> Option<uint> {
+        self.known_number("colors")
+    }
+
+    /// The maximum number of color pairs the terminal supports (`pairs`).
+    pub fn max_pairs (&self) -> Option<uint> {
+        self.known_number("pairs")
+    }
+
+    /// Whether the cursor wraps at the right margin (`am`).
+    pub fn auto_right_margin (&self) -> bool {
+        self.known_flag("am")
+    }
+
+    /// Whether a newline is ignored after wrapping at the margin (`xenl`).
+    pub fn eat_newline_glitch (&self) -> bool {
+        self.known_flag("xenl")
+    }
+
+    /// Whether erasing fills with the current background color (`bce`).
+    pub fn back_color_erase (&self) -> bool {
+        self.known_flag("bce")
+    }
+
+    /// Whether it is safe to move the cursor in standout mode (`msgr`).
+    pub fn move_standout_mode (&self) -> bool {
+        self.known_flag("msgr")
+    }
+
+    /// Whether the terminal has a meta key (`km`).
+    pub fn has_meta_key (&self) -> bool {
+        self.known_flag("km")
+    }
+
+    /**
+     * Whether the terminal supports direct (24-bit) colors.
+     *
+     * This is indicated by the user-defined `RGB` capability, which can be a
+     * boolean, a number (of bits per color), or a string.
+     */
+    pub fn direct_color (&self) -> bool {
+        self.known_flag("RGB")
+            || self.known_number("RGB").is_some()
+            || self.escape("RGB").unwrap().is_some()
+    }
+
+    /// The terminal escape corresponding to the `name` terminfo capability.
+    pub fn escape (&self, name: &str) -> InfoResult<Option<String>> {
+        match self.entry.strings.find_equiv(&name) {
+            Some(s) => Ok(Some(s.clone())),
+            None if compiled::is_string_cap(name) => Ok(None),
+            None    => Err(UnknownCapability(name.to_string())),
+        }
+    }
+
+    /**
+     * The terminal escape corresponding to the `name` terminfo capability.
+     *
+     * This capability must take one parameter, which should be passed as
+     * `p1`.
+     */
+    pub fn escape1 (&self, name: &str, p1: int) -> InfoResult<Option<String>> {
+        self.escapen(name, &[p1])
+    }
+
+    /**
+     * The terminal escape corresponding to the `name` terminfo capability.
+     *
+     * This capability must take two parameters, which should be passed as
+     * `p1` and `p2`.
+     */
+    pub fn escape2 (&self, name: &str, p1: int, p2: int)
+                    -> InfoResult<Option<String>> {
+        self.escapen(name, &[p1, p2])
+    }
+
+    /**
+     * The terminal escape corresponding to the `name` terminfo capability.
+     *
+     * This capability can take up to nine parameters, which should be passed
+     * in `params`.
+     */
+    pub fn escapen (&self, name: &str, params: &[int])
+                    -> InfoResult<Option<String>> {
+        let s = match try!(self.escape(name)) {
+            Some(s) => s,
+            None    => return Ok(None),
+        };
+        let params: Vec<params::Param> = params.iter().map(|&p| {
+            params::Number(p)
+        }).collect();
+        let mut vars = self.vars.borrow_mut();
+        match params::expand(s.as_slice(), params.as_slice(), &mut *vars) {
+            Ok(r)  => Ok(Some(r)),
+            Err(e) => Err(ParameterError(format!("{:s}: {:s}", name, e))),
+        }
+    }
 }
 
-pub fn escape1 (name: &str, p1: int) -> Option<String> {
-    escape(name).map(|s| { tparm1(s.as_slice(), p1) })
+// the terminal used by the free functions in this module
+local_data_key!(default_info: TermInfo)
+
+/**
+ * Initialize the terminfo database.
+ *
+ * This loads the capabilities of the current terminal (see
+ * `TermInfo::from_env`) for use by the free functions in this module. It is
+ * done automatically the first time one of them is called, but calling it
+ * again will pick up changes to `TERM`.
+ */
+pub fn init () -> InfoResult<()> {
+    let info = try!(TermInfo::from_env());
+    default_info.replace(Some(info));
+    Ok(())
 }
 
-pub fn escape2 (name: &str, p1: int, p2: int) -> Option<String> {
-    escape(name).map(|s| { tparm2(s.as_slice(), p1, p2) })
+fn with_default<T> (f: |&TermInfo| -> InfoResult<T>) -> InfoResult<T> {
+    if default_info.get().is_none() {
+        try!(init());
+    }
+    let info = default_info.get().unwrap();
+    f(&*info)
 }
 
-fn escape_cap (cap: Capability) -> Option<String> {
-    // TODO warning and default to xterm, maybe?
-    let termname = os::getenv("TERM").expect(
-        "The TERM environment variable is not set"
-    );
-    // TODO can we actually dynamically load libcurses and fall back to that
-    // if it exists?
-    let term = name_to_term(termname.as_slice()).expect(
-        format!("Unknown terminal type {:s}", termname).as_slice()
-    );
+pub fn escape (name: &str) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escape(name) })
+}
+
+pub fn escape1 (name: &str, p1: int) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escape1(name, p1) })
+}
+
+pub fn escape2 (name: &str, p1: int, p2: int) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escape2(name, p1, p2) })
+}
+
+pub fn escapen (name: &str, params: &[int]) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escapen(name, params) })
+}
+
+pub fn flag (name: &str) -> InfoResult<bool> {
+    with_default(|info| { info.flag(name) })
+}
+
+pub fn number (name: &str) -> InfoResult<Option<int>> {
+    with_default(|info| { info.number(name) })
+}
+
+// turns a missing capability into an error, for the functions below which
+// are only useful if the terminal has it
+fn required (name: &str, escape: InfoResult<Option<String>>)
+             -> InfoResult<String> {
+    match try!(escape) {
+        Some(e) => Ok(e),
+        None    => Err(UnsupportedCapability(name.to_string())),
+    }
+}
+
+fn escape_cap (term: Term, cap: Capability) -> Option<String> {
     db[term as uint][cap as uint].map(|s| { s.to_owned() })
 }
 
@@ -779,67 +1248,41 @@ fn name_to_cap (name: &str) -> Option<Capability> {
     }
 }
 
-fn tparm1 (s: &str, p1: int) -> String {
-    tparm2(s, p1, 0)
-}
-
-// XXX awful implementation
-fn tparm2 (s: &str, p1: int, p2: int) -> String {
-    let mut r = s.to_owned();
-    let mut a1 = p1;
-    let mut a2 = p2;
-
-    if r.as_slice().contains("%i") {
-        r = str::replace(r.as_slice(), "%i", "");
-        a1 += 1;
-        a2 += 1;
-    }
-    r = str::replace(r.as_slice(), "%p1%d", format!("{:d}", a1).as_slice());
-    r = str::replace(r.as_slice(), "%p2%d", format!("{:d}", a2).as_slice());
-    let mut i = 0;
-    while i < r.len() - 1 {
-        if r.as_slice().char_at(i) == '%' {
-            if r.as_slice().char_at(i + 1) == '%' {
-                i += 2;
-            }
-            else {
-                fail!(format!("Unknown escape sequence {:s}", r.as_slice().slice(i, i + 2)));
-            }
-        }
-        i += 1;
-    }
-    r = str::replace(r.as_slice(), "%%", "%");
-    r
-}
-
 macro_rules! def_escape(
     ($name:ident -> $escape:expr) => (
-        pub fn $name () -> String {
+        pub fn $name () -> InfoResult<String> {
             let attr = $escape;
-            match escape(attr) {
-                Some(e) => e,
-                None    => fail!(format!("{:s} is not supported on this terminal", attr)),
-            }
+            required(attr, escape(attr))
         }
     );
     ($name:ident -> $escape:expr, $ty1:ident) => (
-        pub fn $name (p1: $ty1) -> String {
+        pub fn $name (p1: $ty1) -> InfoResult<String> {
             let attr = $escape;
-            match escape1(attr, p1 as int) {
-                Some(e) => e,
-                None    => fail!(format!("{:s} is not supported on this terminal",
-                                      attr)),
-            }
+            required(attr, escape1(attr, p1 as int))
         }
     );
     ($name:ident -> $escape:expr, $ty1:ident, $ty2:ident) => (
-        pub fn $name (p1: $ty1, p2: $ty2) -> String {
+        pub fn $name (p1: $ty1, p2: $ty2) -> InfoResult<String> {
             let attr = $escape;
-            match escape2(attr, p1 as int, p2 as int) {
-                Some(e) => e,
-                None    => fail!(format!("{:s} is not supported on this terminal",
-                                      attr)),
-            }
+            required(attr, escape2(attr, p1 as int, p2 as int))
+        }
+    );
+)
+
+macro_rules! def_number(
+    ($name:ident -> $cap:expr) => (
+        pub fn $name () -> InfoResult<Option<uint>> {
+            with_default(|info| {
+                info.number($cap).map(|n| { n.map(|n| { n as uint }) })
+            })
+        }
+    );
+)
+
+macro_rules! def_flag(
+    ($name:ident -> $cap:expr) => (
+        pub fn $name () -> InfoResult<bool> {
+            with_default(|info| { info.flag($cap) })
         }
     );
 )
@@ -847,6 +1290,33 @@ macro_rules! def_escape(
 // XXX macros can't take attributes yet (including documentation), so change
 // these to /// once that is fixed
 
+// The number of columns on the terminal.
+def_number!(columns            -> "cols")
+// The number of lines on the terminal.
+def_number!(lines              -> "lines")
+// The initial spacing of tab stops.
+def_number!(init_tabs          -> "it")
+// The maximum number of colors the terminal can display.
+def_number!(max_colors         -> "colors")
+// The maximum number of color pairs the terminal supports.
+def_number!(max_pairs          -> "pairs")
+
+// Whether the cursor wraps at the right margin.
+def_flag!(auto_right_margin    -> "am")
+// Whether a newline is ignored after wrapping at the margin.
+def_flag!(eat_newline_glitch   -> "xenl")
+// Whether erasing fills with the current background color.
+def_flag!(back_color_erase     -> "bce")
+// Whether it is safe to move the cursor in standout mode.
+def_flag!(move_standout_mode   -> "msgr")
+// Whether the terminal has a meta key.
+def_flag!(has_meta_key         -> "km")
+
+/// Whether the terminal supports direct (24-bit) colors.
+pub fn direct_color () -> InfoResult<bool> {
+    with_default(|info| { Ok(info.direct_color()) })
+}
+
 // The terminal escape to clear the screen.
 def_escape!(clear_screen         -> "clear")
 // The terminal escape to set the foreground color to `p1`.
@@ -944,10 +1414,52 @@ def_escape!(key_f11         -> "kf11")
 def_escape!(key_f12         -> "kf12")
 
 /// The terminal escape generated by the F<`n`> key.
-pub fn key_f (n: uint) -> String {
+pub fn key_f (n: uint) -> InfoResult<String> {
     let attr = format!("kf{:u}", n);
-    match escape(attr.as_slice()) {
-        Some(e) => e,
-        None    => fail!(format!("{:s} is not supported on this terminal", attr)),
+    required(attr.as_slice(), escape(attr.as_slice()))
+}
+
+#[test]
+fn test_builtin_table () {
+    let xterm = TermInfo::from_table("xterm", Xterm);
+    assert!(xterm.names() == vec!("xterm".to_string()).as_slice());
+    assert!(xterm.escape("bold") == Ok(Some("\x1b[1m".to_string())));
+    assert!(xterm.escape2("cup", 4, 9) == Ok(Some("\x1b[5;10H".to_string())));
+    assert!(xterm.escape1("indn", 3) == Ok(Some("\x1b[3S".to_string())));
+    assert!(xterm.escape("sitm") == Ok(None));
+
+    assert!(xterm.max_colors() == Some(8));
+    assert!(xterm.columns() == Some(80));
+    assert!(xterm.auto_right_margin());
+    assert!(xterm.back_color_erase());
+    assert!(!xterm.direct_color());
+
+    let linux = TermInfo::from_table("linux", Linux);
+    assert!(linux.columns().is_none());
+    assert!(!linux.has_meta_key());
+    assert!(linux.escape("indn") == Ok(None));
+    assert!(linux.escape("smcup") == Ok(None));
+    assert!(linux.escape("kf1") == Ok(Some("\x1b[[A".to_string())));
+}
+
+#[test]
+fn test_errors () {
+    let xterm = TermInfo::from_table("xterm", Xterm);
+    assert!(xterm.escape("notacap")
+            == Err(UnknownCapability("notacap".to_string())));
+    assert!(xterm.flag("notacap")
+            == Err(UnknownCapability("notacap".to_string())));
+    assert!(xterm.number("notacap")
+            == Err(UnknownCapability("notacap".to_string())));
+    assert!(required("sitm", xterm.escape("sitm"))
+            == Err(UnsupportedCapability("sitm".to_string())));
+
+    match TermInfo::from_name("no-such-terminal") {
+        Err(UnknownTerminal(name)) => assert!(name.as_slice() == "no-such-terminal"),
+        _                          => fail!(),
+    }
+    match TermInfo::from_bytes(&[0x1a, 0x01, 0x00]) {
+        Err(DatabaseError(_)) => (),
+        _                     => fail!(),
     }
 }
@@ -0,0 +1,639 @@
+use std::{os, str};
+use std::collections::HashMap;
+use std::io::File;
+use std::path::posix::Path;
+
+/// The capabilities of a terminal, as read from a compiled terminfo file.
+pub struct Entry {
+    pub names: Vec<String>,
+    pub bools: HashMap<String, bool>,
+    pub numbers: HashMap<String, int>,
+    pub strings: HashMap<String, String>,
+}
+
+// the magic numbers at the start of a compiled entry. the second one is used
+// by ncurses 6.1 and later for entries with numbers that don't fit in an i16
+static LegacyMagic: i16 = 0x011a;
+static ExtendedNumberMagic: i16 = 0x021e;
+
+static SystemDirs: [&'static str, ..4] = [
+    "/etc/terminfo",
+    "/lib/terminfo",
+    "/usr/share/terminfo",
+    "/usr/lib/terminfo",
+];
+
+// NOTE: these are in the order used by the compiled format, and must not be
+// rearranged (taken from the boolnames/numnames/strnames arrays in ncurses)
+pub static BoolNames: [&'static str, ..44] = [
+    "bw", "am", "xsb", "xhp", "xenl", "eo", "gn", "hc", "km", "hs", "in",
+    "da", "db", "mir", "msgr", "os", "eslok", "xt", "hz", "ul", "xon",
+    "nxon", "mc5i", "chts", "nrrmc", "npc", "ndscr", "ccc", "bce", "hls",
+    "xhpa", "crxm", "daisy", "xvpa", "sam", "cpix", "lpix", "OTbs", "OTns",
+    "OTnc", "OTMT", "OTNL", "OTpt", "OTxr",
+];
+
+pub static NumNames: [&'static str, ..39] = [
+    "cols", "it", "lines", "lm", "xmc", "pb", "vt", "wsl", "nlab", "lh",
+    "lw", "ma", "wnum", "colors", "pairs", "ncv", "bufsz", "spinv", "spinh",
+    "maddr", "mjump", "mcs", "mls", "npins", "orc", "orl", "orhi", "orvi",
+    "cps", "widcs", "btns", "bitwin", "bitype", "OTug", "OTdC", "OTdN",
+    "OTdB", "OTdT", "OTkn",
+];
+
+pub static StrNames: [&'static str, ..414] = [
+    "cbt", "bel", "cr", "csr", "tbc", "clear", "el", "ed", "hpa", "cmdch",
+    "cup", "cud1", "home", "civis", "cub1", "mrcup", "cnorm", "cuf1", "ll",
+    "cuu1", "cvvis", "dch1", "dl1", "dsl", "hd", "smacs", "blink", "bold",
+    "smcup", "smdc", "dim", "smir", "invis", "prot", "rev", "smso", "smul",
+    "ech", "rmacs", "sgr0", "rmcup", "rmdc", "rmir", "rmso", "rmul", "flash",
+    "ff", "fsl", "is1", "is2", "is3", "if", "ich1", "il1", "ip", "kbs",
+    "ktbc", "kclr", "kctab", "kdch1", "kdl1", "kcud1", "krmir", "kel", "ked",
+    "kf0", "kf1", "kf10", "kf2", "kf3", "kf4", "kf5", "kf6", "kf7", "kf8",
+    "kf9", "khome", "kich1", "kil1", "kcub1", "kll", "knp", "kpp", "kcuf1",
+    "kind", "kri", "khts", "kcuu1", "rmkx", "smkx", "lf0", "lf1", "lf10",
+    "lf2", "lf3", "lf4", "lf5", "lf6", "lf7", "lf8", "lf9", "rmm", "smm",
+    "nel", "pad", "dch", "dl", "cud", "ich", "indn", "il", "cub", "cuf",
+    "rin", "cuu", "pfkey", "pfloc", "pfx", "mc0", "mc4", "mc5", "rep", "rs1",
+    "rs2", "rs3", "rf", "rc", "vpa", "sc", "ind", "ri", "sgr", "hts", "wind",
+    "ht", "tsl", "uc", "hu", "iprog", "ka1", "ka3", "kb2", "kc1", "kc3",
+    "mc5p", "rmp", "acsc", "pln", "kcbt", "smxon", "rmxon", "smam", "rmam",
+    "xonc", "xoffc", "enacs", "smln", "rmln", "kbeg", "kcan", "kclo", "kcmd",
+    "kcpy", "kcrt", "kend", "kent", "kext", "kfnd", "khlp", "kmrk", "kmsg",
+    "kmov", "knxt", "kopn", "kopt", "kprv", "kprt", "krdo", "kref", "krfr",
+    "krpl", "krst", "kres", "ksav", "kspd", "kund", "kBEG", "kCAN", "kCMD",
+    "kCPY", "kCRT", "kDC", "kDL", "kslt", "kEND", "kEOL", "kEXT", "kFND",
+    "kHLP", "kHOM", "kIC", "kLFT", "kMSG", "kMOV", "kNXT", "kOPT", "kPRV",
+    "kPRT", "kRDO", "kRPL", "kRIT", "kRES", "kSAV", "kSPD", "kUND", "rfi",
+    "kf11", "kf12", "kf13", "kf14", "kf15", "kf16", "kf17", "kf18", "kf19",
+    "kf20", "kf21", "kf22", "kf23", "kf24", "kf25", "kf26", "kf27", "kf28",
+    "kf29", "kf30", "kf31", "kf32", "kf33", "kf34", "kf35", "kf36", "kf37",
+    "kf38", "kf39", "kf40", "kf41", "kf42", "kf43", "kf44", "kf45", "kf46",
+    "kf47", "kf48", "kf49", "kf50", "kf51", "kf52", "kf53", "kf54", "kf55",
+    "kf56", "kf57", "kf58", "kf59", "kf60", "kf61", "kf62", "kf63", "el1",
+    "mgc", "smgl", "smgr", "fln", "sclk", "dclk", "rmclk", "cwin", "wingo",
+    "hup", "dial", "qdial", "tone", "pulse", "hook", "pause", "wait", "u0",
+    "u1", "u2", "u3", "u4", "u5", "u6", "u7", "u8", "u9", "op", "oc",
+    "initc", "initp", "scp", "setf", "setb", "cpi", "lpi", "chr", "cvr",
+    "defc", "swidm", "sdrfq", "sitm", "slm", "smicm", "snlq", "snrmq",
+    "sshm", "ssubm", "ssupm", "sum", "rwidm", "ritm", "rlm", "rmicm", "rshm",
+    "rsubm", "rsupm", "rum", "mhpa", "mcud1", "mcub1", "mcuf1", "mvpa",
+    "mcuu1", "porder", "mcud", "mcub", "mcuf", "mcuu", "scs", "smgb",
+    "smgbp", "smglp", "smgrp", "smgt", "smgtp", "sbim", "scsd", "rbim",
+    "rcsd", "subcs", "supcs", "docr", "zerom", "csnm", "kmous", "minfo",
+    "reqmp", "getm", "setaf", "setab", "pfxl", "devt", "csin", "s0ds",
+    "s1ds", "s2ds", "s3ds", "smglr", "smgtb", "birep", "binel", "bicr",
+    "colornm", "defbi", "endbi", "setcolor", "slines", "dispc", "smpch",
+    "rmpch", "smsc", "rmsc", "pctrm", "scesc", "scesa", "ehhlm", "elhlm",
+    "elohlm", "erhlm", "ethlm", "evhlm", "sgr1", "slength", "OTi2", "OTrs",
+    "OTnl", "OTbc", "OTko", "OTma", "OTG2", "OTG3", "OTG1", "OTG4", "OTGR",
+    "OTGL", "OTGU", "OTGD", "OTGH", "OTGV", "OTGC", "meml", "memu", "box1",
+];
+
+// commonly used user-defined capabilities (see user_caps(5)). these aren't
+// in the standard lists, but terminals not having them isn't an error
+pub static ExtendedNames: [&'static str, ..22] = [
+    "AX", "BD", "BE", "Cr", "Cs", "E3", "Ms", "PE", "PS", "RGB", "Se",
+    "Setulc", "Smol", "Rmol", "Smulx", "Ss", "Sync", "Tc", "rmxx", "smxx",
+    "setrgbf", "setrgbb",
+];
+
+/// Returns whether `name` is a known boolean capability.
+pub fn is_bool_cap (name: &str) -> bool {
+    BoolNames.iter().any(|n| { *n == name }) || is_extended_cap(name)
+}
+
+/// Returns whether `name` is a known numeric capability.
+pub fn is_number_cap (name: &str) -> bool {
+    NumNames.iter().any(|n| { *n == name }) || is_extended_cap(name)
+}
+
+/// Returns whether `name` is a known string capability.
+pub fn is_string_cap (name: &str) -> bool {
+    StrNames.iter().any(|n| { *n == name }) || is_extended_cap(name)
+}
+
+fn is_extended_cap (name: &str) -> bool {
+    ExtendedNames.iter().any(|n| { *n == name })
+}
+
+/// Loads the compiled terminfo entry at `path`.
+pub fn load_file (path: &Path) -> Result<Entry, String> {
+    let bytes = match File::open(path).read_to_end() {
+        Ok(b)  => b,
+        Err(e) => return Err(format!("couldn't read {}: {}", path.display(), e)),
+    };
+    parse(bytes.as_slice())
+}
+
+/**
+ * Returns the path of the compiled terminfo entry for `name`, if any.
+ *
+ * The entry is searched for in `$TERMINFO`, `~/.terminfo`, each directory
+ * in `$TERMINFO_DIRS`, and then the standard system directories, in that
+ * order.
+ */
+pub fn find (name: &str) -> Option<Path> {
+    if name.len() == 0 || name.contains_char('/') {
+        return None;
+    }
+
+    let first = name.char_at(0);
+    for dir in search_dirs().iter() {
+        // some systems (os x, mostly) use the hex value of the first
+        // character for the subdirectory instead of the character itself
+        let candidates = [
+            dir.join(str::from_char(first).as_slice()).join(name),
+            dir.join(format!("{:x}", first as uint).as_slice()).join(name),
+        ];
+        for path in candidates.iter() {
+            if path.exists() {
+                return Some(path.clone());
+            }
+        }
+    }
+
+    None
+}
+
+fn search_dirs () -> Vec<Path> {
+    let mut dirs = vec!();
+
+    match os::getenv("TERMINFO") {
+        Some(dir) => dirs.push(Path::new(dir)),
+        None      => (),
+    }
+    match os::homedir() {
+        Some(home) => dirs.push(home.join(".terminfo")),
+        None       => (),
+    }
+    match os::getenv("TERMINFO_DIRS") {
+        Some(list) => {
+            // an empty element means "the system directories"
+            for dir in list.as_slice().split(':') {
+                if dir.len() == 0 {
+                    push_system_dirs(&mut dirs);
+                }
+                else {
+                    dirs.push(Path::new(dir));
+                }
+            }
+        }
+        None       => (),
+    }
+    push_system_dirs(&mut dirs);
+
+    dirs
+}
+
+fn push_system_dirs (dirs: &mut Vec<Path>) {
+    for dir in SystemDirs.iter() {
+        dirs.push(Path::new(*dir));
+    }
+}
+
+/**
+ * Parses a compiled terminfo entry.
+ *
+ * Both the legacy format (with 16-bit numbers) and the format used by newer
+ * versions of ncurses for entries with larger numbers (with 32-bit numbers)
+ * are supported, as are user-defined capabilities in the extended section.
+ */
+pub fn parse (bytes: &[u8]) -> Result<Entry, String> {
+    let mut r = EntryReader { bytes: bytes, pos: 0 };
+
+    let magic = try!(r.read_i16());
+    let number_size = if magic == LegacyMagic {
+        2u
+    }
+    else if magic == ExtendedNumberMagic {
+        4u
+    }
+    else {
+        return Err(format!("invalid magic number {:x}", magic));
+    };
+
+    let names_size = try!(r.read_count());
+    let bool_count = try!(r.read_count());
+    let num_count  = try!(r.read_count());
+    let str_count  = try!(r.read_count());
+    let table_size = try!(r.read_count());
+
+    if bool_count > BoolNames.len()
+        || num_count > NumNames.len()
+        || str_count > StrNames.len() {
+        return Err("too many capabilities in terminfo entry".to_string());
+    }
+
+    let names_bytes = try!(r.read_bytes(names_size));
+    let names_str = try!(string_at(names_bytes, 0));
+    let names = names_str.as_slice().split('|').map(|s| {
+        s.to_string()
+    }).collect();
+
+    let mut entry = Entry {
+        names: names,
+        bools: HashMap::new(),
+        numbers: HashMap::new(),
+        strings: HashMap::new(),
+    };
+
+    for i in range(0, bool_count) {
+        if try!(r.read_u8()) == 1 {
+            entry.bools.insert(BoolNames[i].to_string(), true);
+        }
+    }
+    r.align();
+
+    for i in range(0, num_count) {
+        let n = try!(r.read_number(number_size));
+        if n >= 0 {
+            entry.numbers.insert(NumNames[i].to_string(), n);
+        }
+    }
+
+    let mut offsets = vec!();
+    for _ in range(0, str_count) {
+        offsets.push(try!(r.read_i16()));
+    }
+    let table = try!(r.read_bytes(table_size));
+    for (i, &offset) in offsets.iter().enumerate() {
+        if offset >= 0 {
+            let s = try!(string_at(table, offset as uint));
+            entry.strings.insert(StrNames[i].to_string(), strip_padding(s));
+        }
+    }
+
+    if r.remaining() > 0 {
+        try!(parse_extended(&mut r, number_size, &mut entry));
+    }
+
+    Ok(entry)
+}
+
+// the extended section holds capabilities that aren't in the standard lists
+// (like RGB, Tc, or Smulx), along with their names
+fn parse_extended (r: &mut EntryReader, number_size: uint,
+                   entry: &mut Entry) -> Result<(), String> {
+    r.align();
+    // some entries have a single byte of padding and nothing else
+    if r.remaining() < 10 {
+        return Ok(());
+    }
+
+    let bool_count = try!(r.read_count());
+    let num_count  = try!(r.read_count());
+    let str_count  = try!(r.read_count());
+    let _          = try!(r.read_count()); // number of offsets in the table
+    let table_size = try!(r.read_count());
+
+    let mut bools = vec!();
+    for _ in range(0, bool_count) {
+        bools.push(try!(r.read_u8()) == 1);
+    }
+    r.align();
+
+    let mut numbers = vec!();
+    for _ in range(0, num_count) {
+        numbers.push(try!(r.read_number(number_size)));
+    }
+
+    let mut offsets = vec!();
+    for _ in range(0, str_count) {
+        offsets.push(try!(r.read_i16()));
+    }
+
+    let mut name_offsets = vec!();
+    for _ in range(0, bool_count + num_count + str_count) {
+        name_offsets.push(try!(r.read_i16()));
+    }
+
+    let table = try!(r.read_bytes(table_size));
+
+    // the names are stored after all of the string values, and their
+    // offsets are relative to the end of the last value
+    let mut values = vec!();
+    let mut names_start = 0u;
+    for &offset in offsets.iter() {
+        if offset >= 0 {
+            let s = try!(string_at(table, offset as uint));
+            let end = offset as uint + s.len() + 1;
+            if end > names_start {
+                names_start = end;
+            }
+            values.push(Some(s));
+        }
+        else {
+            values.push(None);
+        }
+    }
+
+    let mut names = vec!();
+    for &offset in name_offsets.iter() {
+        if offset < 0 {
+            return Err("invalid extended capability name".to_string());
+        }
+        names.push(try!(string_at(table, names_start + offset as uint)));
+    }
+
+    let mut names = names.move_iter();
+    for &b in bools.iter() {
+        let name = names.next().unwrap();
+        if b {
+            entry.bools.insert(name, true);
+        }
+    }
+    for &n in numbers.iter() {
+        let name = names.next().unwrap();
+        if n >= 0 {
+            entry.numbers.insert(name, n);
+        }
+    }
+    for value in values.move_iter() {
+        let name = names.next().unwrap();
+        match value {
+            Some(s) => { entry.strings.insert(name, strip_padding(s)); }
+            None    => (),
+        }
+    }
+
+    Ok(())
+}
+
+// reads a nul-terminated string starting at `offset`
+fn string_at (table: &[u8], offset: uint) -> Result<String, String> {
+    if offset > table.len() {
+        return Err(format!("string offset {:u} out of range", offset));
+    }
+    let rest = table.slice_from(offset);
+    match rest.iter().position(|&b| { b == 0 }) {
+        Some(end) => {
+            Ok(String::from_utf8_lossy(rest.slice_to(end)).into_string())
+        }
+        None      => Err("unterminated string in terminfo entry".to_string()),
+    }
+}
+
+// removes the `$<...>` delays from a string capability. they only matter to
+// terminals which need time to carry out an escape, and they would otherwise
+// end up being written out literally.
+fn strip_padding (s: String) -> String {
+    if !s.as_slice().contains("$<") {
+        return s;
+    }
+
+    let chars: Vec<char> = s.as_slice().chars().collect();
+    let mut out = String::new();
+    let mut i = 0u;
+    while i < chars.len() {
+        match padding_len(chars.slice_from(i)) {
+            Some(len) => i += len,
+            None      => {
+                out.push_char(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// the length of the delay (like `$<5>` or `$<100*/>`) at the start of `chars`
+fn padding_len (chars: &[char]) -> Option<uint> {
+    if chars.len() < 4 || chars[0] != '$' || chars[1] != '<' {
+        return None;
+    }
+
+    let mut i = 2u;
+    let mut digits = 0u;
+    while i < chars.len() && (chars[i].is_digit() || chars[i] == '.') {
+        if chars[i] != '.' {
+            digits += 1;
+        }
+        i += 1;
+    }
+    while i < chars.len() && (chars[i] == '*' || chars[i] == '/') {
+        i += 1;
+    }
+
+    if digits > 0 && i < chars.len() && chars[i] == '>' {
+        Some(i + 1)
+    }
+    else {
+        None
+    }
+}
+
+struct EntryReader<'a> {
+    bytes: &'a [u8],
+    pos: uint,
+}
+
+impl<'a> EntryReader<'a> {
+    fn remaining (&self) -> uint {
+        self.bytes.len() - self.pos
+    }
+
+    fn align (&mut self) {
+        if self.pos % 2 == 1 && self.pos < self.bytes.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn read_bytes (&mut self, len: uint) -> Result<&'a [u8], String> {
+        if self.remaining() < len {
+            return Err("truncated terminfo entry".to_string());
+        }
+        let bytes = self.bytes.slice(self.pos, self.pos + len);
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_u8 (&mut self) -> Result<u8, String> {
+        let bytes = try!(self.read_bytes(1));
+        Ok(bytes[0])
+    }
+
+    fn read_i16 (&mut self) -> Result<i16, String> {
+        let bytes = try!(self.read_bytes(2));
+        Ok((bytes[0] as u16 | ((bytes[1] as u16) << 8)) as i16)
+    }
+
+    fn read_i32 (&mut self) -> Result<i32, String> {
+        let bytes = try!(self.read_bytes(4));
+        Ok((bytes[0] as u32
+            | ((bytes[1] as u32) << 8)
+            | ((bytes[2] as u32) << 16)
+            | ((bytes[3] as u32) << 24)) as i32)
+    }
+
+    fn read_count (&mut self) -> Result<uint, String> {
+        let n = try!(self.read_i16());
+        if n < 0 {
+            return Err(format!("invalid count {:d} in terminfo entry", n));
+        }
+        Ok(n as uint)
+    }
+
+    // absent numbers are -1 and cancelled numbers are -2
+    fn read_number (&mut self, size: uint) -> Result<int, String> {
+        if size == 4 {
+            Ok(try!(self.read_i32()) as int)
+        }
+        else {
+            Ok(try!(self.read_i16()) as int)
+        }
+    }
+}
+
+// compiled with tic from:
+//   rtest|rust-term test terminal,
+//       am, xenl,
+//       cols#80, lines#24,
+//       bold=\E[1m, cup=\E[%i%p1%d;%p2%dH, sgr0=\E[m,
+#[cfg(test)]
+static LegacyEntry: [u8, ..160] = [
+    0x1a, 0x01, 0x1e, 0x00, 0x05, 0x00, 0x03, 0x00, 0x28, 0x00, 0x1a, 0x00,
+    0x72, 0x74, 0x65, 0x73, 0x74, 0x7c, 0x72, 0x75, 0x73, 0x74, 0x2d, 0x74,
+    0x65, 0x72, 0x6d, 0x20, 0x74, 0x65, 0x73, 0x74, 0x20, 0x74, 0x65, 0x72,
+    0x6d, 0x69, 0x6e, 0x61, 0x6c, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00,
+    0x50, 0x00, 0xff, 0xff, 0x18, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x11, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x16, 0x00, 0x1b, 0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b,
+    0x25, 0x70, 0x32, 0x25, 0x64, 0x48, 0x00, 0x1b, 0x5b, 0x31, 0x6d, 0x00,
+    0x1b, 0x5b, 0x6d, 0x00,
+];
+
+// compiled with tic -x from:
+//   rtest2|rust-term extended test terminal,
+//       am, Tc,
+//       colors#0x1000000, pairs#0x10000,
+//       bold=\E[1m, Smulx=\E[4:%p1%dm,
+#[cfg(test)]
+static ExtendedEntry: [u8, ..214] = [
+    0x1e, 0x02, 0x28, 0x00, 0x02, 0x00, 0x0f, 0x00, 0x1c, 0x00, 0x05, 0x00,
+    0x72, 0x74, 0x65, 0x73, 0x74, 0x32, 0x7c, 0x72, 0x75, 0x73, 0x74, 0x2d,
+    0x74, 0x65, 0x72, 0x6d, 0x20, 0x65, 0x78, 0x74, 0x65, 0x6e, 0x64, 0x65,
+    0x64, 0x20, 0x74, 0x65, 0x73, 0x74, 0x20, 0x74, 0x65, 0x72, 0x6d, 0x69,
+    0x6e, 0x61, 0x6c, 0x00, 0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+    0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x00, 0x1b, 0x5b, 0x31, 0x6d, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x03, 0x00, 0x14, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x03, 0x00, 0x1b, 0x5b, 0x34, 0x3a, 0x25, 0x70, 0x31, 0x25, 0x64, 0x6d,
+    0x00, 0x54, 0x63, 0x00, 0x53, 0x6d, 0x75, 0x6c, 0x78, 0x00,
+];
+
+// compiled with tic from:
+//   rtest3|rust-term padded test terminal,
+//       clear=\E[H\E[J$<50*>, cup=\E[%i%p1%d;%p2%dH$<5>,
+//       flash=\E[?5h$<100/>\E[?5l,
+#[cfg(test)]
+static PaddedEntry: [u8, ..194] = [
+    0x1a, 0x01, 0x26, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2e, 0x00, 0x34, 0x00,
+    0x72, 0x74, 0x65, 0x73, 0x74, 0x33, 0x7c, 0x72, 0x75, 0x73, 0x74, 0x2d,
+    0x74, 0x65, 0x72, 0x6d, 0x20, 0x70, 0x61, 0x64, 0x64, 0x65, 0x64, 0x20,
+    0x74, 0x65, 0x73, 0x74, 0x20, 0x74, 0x65, 0x72, 0x6d, 0x69, 0x6e, 0x61,
+    0x6c, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0d, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x22, 0x00, 0x1b, 0x5b,
+    0x48, 0x1b, 0x5b, 0x4a, 0x24, 0x3c, 0x35, 0x30, 0x2a, 0x3e, 0x00, 0x1b,
+    0x5b, 0x25, 0x69, 0x25, 0x70, 0x31, 0x25, 0x64, 0x3b, 0x25, 0x70, 0x32,
+    0x25, 0x64, 0x48, 0x24, 0x3c, 0x35, 0x3e, 0x00, 0x1b, 0x5b, 0x3f, 0x35,
+    0x68, 0x24, 0x3c, 0x31, 0x30, 0x30, 0x2f, 0x3e, 0x1b, 0x5b, 0x3f, 0x35,
+    0x6c, 0x00,
+];
+
+#[test]
+fn test_parse_legacy () {
+    let entry = parse(LegacyEntry.as_slice()).unwrap();
+
+    assert!(entry.names == vec!("rtest".to_string(),
+                                "rust-term test terminal".to_string()));
+
+    check_bool(&entry, "am", true);
+    check_bool(&entry, "xenl", true);
+    check_bool(&entry, "bw", false);
+
+    check_number(&entry, "cols", Some(80));
+    check_number(&entry, "lines", Some(24));
+    check_number(&entry, "colors", None);
+
+    check_string(&entry, "bold", Some("\x1b[1m"));
+    check_string(&entry, "cup", Some("\x1b[%i%p1%d;%p2%dH"));
+    check_string(&entry, "sgr0", Some("\x1b[m"));
+    check_string(&entry, "smul", None);
+}
+
+#[test]
+fn test_parse_extended () {
+    let entry = parse(ExtendedEntry.as_slice()).unwrap();
+
+    assert!(entry.names == vec!("rtest2".to_string(),
+                                "rust-term extended test terminal".to_string()));
+
+    check_bool(&entry, "am", true);
+    check_bool(&entry, "Tc", true);
+
+    check_number(&entry, "colors", Some(0x1000000));
+    check_number(&entry, "pairs", Some(0x10000));
+
+    check_string(&entry, "bold", Some("\x1b[1m"));
+    check_string(&entry, "Smulx", Some("\x1b[4:%p1%dm"));
+}
+
+#[test]
+fn test_parse_padding () {
+    let entry = parse(PaddedEntry.as_slice()).unwrap();
+
+    check_string(&entry, "clear", Some("\x1b[H\x1b[J"));
+    check_string(&entry, "cup", Some("\x1b[%i%p1%d;%p2%dH"));
+    check_string(&entry, "flash", Some("\x1b[?5h\x1b[?5l"));
+
+    // anything which isn't a well-formed delay is left alone
+    assert!(strip_padding("$<>".to_string()) == "$<>".to_string());
+    assert!(strip_padding("a$<5".to_string()) == "a$<5".to_string());
+    assert!(strip_padding("$<1.5*/>x".to_string()) == "x".to_string());
+}
+
+#[test]
+fn test_parse_invalid () {
+    assert!(parse(&[]).is_err());
+    assert!(parse(&[0x1a, 0x02, 0x00, 0x00]).is_err());
+    assert!(parse(LegacyEntry.slice_to(100)).is_err());
+}
+
+#[cfg(test)]
+fn check_bool (entry: &Entry, name: &str, value: bool) {
+    assert!(entry.bools.find_equiv(&name).is_some() == value);
+}
+
+#[cfg(test)]
+fn check_number (entry: &Entry, name: &str, value: Option<int>) {
+    assert!(entry.numbers.find_equiv(&name).map(|n| { *n }) == value);
+}
+
+#[cfg(test)]
+fn check_string (entry: &Entry, name: &str, value: Option<&str>) {
+    match (entry.strings.find_equiv(&name), value) {
+        (Some(s), Some(v)) => { assert!(s.as_slice() == v) }
+        (None, None)       => (),
+        _                  => fail!(format!("wrong value for {:s}", name)),
+    }
+}
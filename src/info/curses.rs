@@ -1,15 +1,28 @@
-use std::str;
-use std::ptr;
-use std::c_str;
-use std::libc::{c_char,c_int,c_long};
+use std::{os, ptr, str};
+use std::path::posix::Path;
+use libc::{c_char,c_int,c_long,c_void};
+
+pub use self::error::{Error, InfoResult, NoTerminal, UnknownTerminal,
+                      UnknownCapability, UnsupportedCapability, ParameterError,
+                      DatabaseError};
+
+// only the loading and lookup functions are used here, since curses does
+// its own searching of the terminfo database
+#[allow(dead_code)]
+mod compiled;
+mod error;
 
 mod c {
-use std::libc::{c_char,c_int,c_long};
+use libc::{c_char,c_int,c_long,c_void};
 #[link(name = "curses")]
     extern {
-        pub fn setupterm (term: *mut c_char, fd: c_int, errret: *mut c_int) -> c_int;
-        pub fn tigetstr (s: *mut c_char) -> *mut c_char;
-        pub fn tparm (s: *mut c_char,
+        pub fn setupterm (term: *const c_char, fd: c_int, errret: *mut c_int) -> c_int;
+        pub fn set_curterm (term: *mut c_void) -> *mut c_void;
+        pub fn del_curterm (term: *mut c_void) -> c_int;
+        pub fn tigetstr (s: *const c_char) -> *mut c_char;
+        pub fn tigetnum (s: *const c_char) -> c_int;
+        pub fn tigetflag (s: *const c_char) -> c_int;
+        pub fn tparm (s: *const c_char,
                 a1: c_long, a2: c_long, a3: c_long,
                 a4: c_long, a5: c_long, a6: c_long,
                 a7: c_long, a8: c_long, a9: c_long) -> *mut c_char;
@@ -18,7 +31,7 @@ use std::libc::{c_char,c_int,c_long};
 
 
 /// The default colors available on a terminal emulator.
-#[deriving(Eq)]
+#[deriving(PartialEq, Eq, Clone, Show)]
 pub enum Color {
     ColorBlack = 0,
     ColorRed,
@@ -30,46 +43,355 @@ pub enum Color {
     ColorWhite,
 }
 
+/**
+ * The capabilities of a particular type of terminal.
+ *
+ * Terminals loaded by name are looked up by curses itself. Entries loaded
+ * with `from_file` or `from_bytes` are parsed directly, since curses can only
+ * load entries from its database, but their escapes are still expanded with
+ * `tparm`.
+ */
+pub struct TermInfo {
+    source: Source,
+}
+
+enum Source {
+    // the TERMINAL structure allocated by setupterm
+    CursesSource(*mut c_void),
+    // an entry read from a compiled terminfo file
+    FileSource(compiled::Entry),
+}
+
+impl TermInfo {
+    /**
+     * Loads the capabilities of the current terminal.
+     *
+     * The current terminal is determined by looking at the `TERM`
+     * environment variable.
+     */
+    pub fn from_env () -> InfoResult<TermInfo> {
+        TermInfo::setup(ptr::null(), "")
+    }
+
+    /// Loads the capabilities of the terminal named `name`.
+    pub fn from_name (name: &str) -> InfoResult<TermInfo> {
+        name.with_c_str(|c_name| { TermInfo::setup(c_name, name) })
+    }
+
+    /// Loads the capabilities from the compiled terminfo entry at `path`.
+    pub fn from_file (path: &Path) -> InfoResult<TermInfo> {
+        match compiled::load_file(path) {
+            Ok(entry) => Ok(TermInfo { source: FileSource(entry) }),
+            Err(e)    => Err(DatabaseError(e)),
+        }
+    }
+
+    /// Loads the capabilities from the contents of a compiled terminfo entry.
+    pub fn from_bytes (bytes: &[u8]) -> InfoResult<TermInfo> {
+        match compiled::parse(bytes) {
+            Ok(entry) => Ok(TermInfo { source: FileSource(entry) }),
+            Err(e)    => Err(DatabaseError(e)),
+        }
+    }
+
+    fn setup (c_name: *const c_char, name: &str) -> InfoResult<TermInfo> {
+        unsafe {
+            let mut err = 0;
+            if c::setupterm(c_name, 1, &mut err) != 0 {
+                return match err {
+                    -1 => Err(DatabaseError(
+                        "The terminfo database could not be found".to_string()
+                    )),
+                    // with a null name, curses looked at TERM itself
+                    _ if c_name.is_null() => match os::getenv("TERM") {
+                        Some(term) => Err(UnknownTerminal(term)),
+                        None       => Err(NoTerminal),
+                    },
+                    _  => Err(UnknownTerminal(name.to_string())),
+                };
+            }
+            // setupterm leaves the new terminal in cur_term, so take it out
+            // of there, since every lookup selects its own terminal
+            let term = c::set_curterm(ptr::mut_null());
+            Ok(TermInfo { source: CursesSource(term) })
+        }
+    }
+
+    /// The terminal escape corresponding to the `name` terminfo capability.
+    pub fn escape (&self, name: &str) -> InfoResult<Option<String>> {
+        match self.source {
+            CursesSource(term) => unsafe {
+                select(term);
+                name.with_c_str(|c_name| {
+                    let e = try!(tigetstr(c_name, name));
+                    if e == ptr::mut_null() {
+                        Ok(None)
+                    }
+                    else {
+                        Ok(Some(str::raw::from_c_str(e as *const c_char)))
+                    }
+                })
+            },
+            FileSource(ref entry) => match entry.strings.find_equiv(&name) {
+                Some(s) => Ok(Some(s.clone())),
+                None if compiled::is_string_cap(name) => Ok(None),
+                None    => Err(UnknownCapability(name.to_string())),
+            },
+        }
+    }
+
+    /**
+     * The terminal escape corresponding to the `name` terminfo capability.
+     *
+     * This capability must take one parameter, which should be passed as
+     * `p1`.
+     */
+    pub fn escape1 (&self, name: &str, p1: int) -> InfoResult<Option<String>> {
+        self.escapen(name, &[p1])
+    }
+
+    /**
+     * The terminal escape corresponding to the `name` terminfo capability.
+     *
+     * This capability must take two parameters, which should be passed as
+     * `p1` and `p2`.
+     */
+    pub fn escape2 (&self, name: &str, p1: int, p2: int)
+                    -> InfoResult<Option<String>> {
+        self.escapen(name, &[p1, p2])
+    }
+
+    /**
+     * The terminal escape corresponding to the `name` terminfo capability.
+     *
+     * This capability can take up to nine parameters, which should be passed
+     * in `params`.
+     */
+    pub fn escapen (&self, name: &str, params: &[int])
+                    -> InfoResult<Option<String>> {
+        let s = match try!(self.escape(name)) {
+            Some(s) => s,
+            None    => return Ok(None),
+        };
+        unsafe {
+            s.with_c_str(|c_cap| {
+                let r = try!(tparmn(c_cap as *mut c_char, name, params));
+                Ok(Some(str::raw::from_c_str(r as *const c_char)))
+            })
+        }
+    }
+
+    /// The value of the boolean capability `name`.
+    pub fn flag (&self, name: &str) -> InfoResult<bool> {
+        match self.source {
+            CursesSource(term) => unsafe {
+                select(term);
+                name.with_c_str(|c_name| {
+                    match c::tigetflag(c_name) {
+                        -1 => Err(UnknownCapability(name.to_string())),
+                        0  => Ok(false),
+                        _  => Ok(true),
+                    }
+                })
+            },
+            FileSource(ref entry) => {
+                if entry.bools.find_equiv(&name).is_some() {
+                    Ok(true)
+                }
+                else if compiled::is_bool_cap(name) {
+                    Ok(false)
+                }
+                else {
+                    Err(UnknownCapability(name.to_string()))
+                }
+            },
+        }
+    }
+
+    /// The value of the numeric capability `name`, if the terminal has it.
+    pub fn number (&self, name: &str) -> InfoResult<Option<int>> {
+        match self.source {
+            CursesSource(term) => unsafe {
+                select(term);
+                name.with_c_str(|c_name| {
+                    match c::tigetnum(c_name) {
+                        -2 => Err(UnknownCapability(name.to_string())),
+                        -1 => Ok(None),
+                        n  => Ok(Some(n as int)),
+                    }
+                })
+            },
+            FileSource(ref entry) => match entry.numbers.find_equiv(&name) {
+                Some(n) => Ok(Some(*n)),
+                None if compiled::is_number_cap(name) => Ok(None),
+                None    => Err(UnknownCapability(name.to_string())),
+            },
+        }
+    }
+
+    // the typed accessors below only use names which are known to exist
+    fn known_number (&self, name: &str) -> Option<uint> {
+        self.number(name).unwrap().map(|n| { n as uint })
+    }
+
+    fn known_flag (&self, name: &str) -> bool {
+        self.flag(name).unwrap()
+    }
+
+    /// The number of columns on the terminal (`cols`).
+    pub fn columns (&self) -> Option<uint> {
+        self.known_number("cols")
+    }
+
+    /// The number of lines on the terminal (`lines`).
+    pub fn lines (&self) -> Option<uint> {
+        self.known_number("lines")
+    }
+
+    /// The initial spacing of tab stops (`it`).
+    pub fn init_tabs (&self) -> Option<uint> {
+        self.known_number("it")
+    }
+
+    /// The maximum number of colors the terminal can display (`colors`).
+    pub fn max_colors (&self) -> Option<uint> {
+        self.known_number("colors")
+    }
+
+    /// The maximum number of color pairs the terminal supports (`pairs`).
+    pub fn max_pairs (&self) -> Option<uint> {
+        self.known_number("pairs")
+    }
+
+    /// Whether the cursor wraps at the right margin (`am`).
+    pub fn auto_right_margin (&self) -> bool {
+        self.known_flag("am")
+    }
+
+    /// Whether a newline is ignored after wrapping at the margin (`xenl`).
+    pub fn eat_newline_glitch (&self) -> bool {
+        self.known_flag("xenl")
+    }
+
+    /// Whether erasing fills with the current background color (`bce`).
+    pub fn back_color_erase (&self) -> bool {
+        self.known_flag("bce")
+    }
+
+    /// Whether it is safe to move the cursor in standout mode (`msgr`).
+    pub fn move_standout_mode (&self) -> bool {
+        self.known_flag("msgr")
+    }
+
+    /// Whether the terminal has a meta key (`km`).
+    pub fn has_meta_key (&self) -> bool {
+        self.known_flag("km")
+    }
+
+    /**
+     * Whether the terminal supports direct (24-bit) colors.
+     *
+     * This is indicated by the user-defined `RGB` capability, which can be a
+     * boolean, a number (of bits per color), or a string. Curses only knows
+     * about user-defined capabilities that the terminal actually has, so
+     * they have to be looked up without failing.
+     */
+    pub fn direct_color (&self) -> bool {
+        match self.source {
+            CursesSource(term) => unsafe {
+                select(term);
+                "RGB".with_c_str(|c_name| {
+                    c::tigetflag(c_name) > 0
+                        || c::tigetnum(c_name) >= 0
+                        || (c::tigetstr(c_name) as int != -1
+                            && c::tigetstr(c_name) != ptr::mut_null())
+                })
+            },
+            FileSource(ref entry) => {
+                entry.bools.find_equiv(&"RGB").is_some()
+                    || entry.numbers.find_equiv(&"RGB").is_some()
+                    || entry.strings.find_equiv(&"RGB").is_some()
+            },
+        }
+    }
+}
+
+impl Drop for TermInfo {
+    fn drop (&mut self) {
+        match self.source {
+            CursesSource(term) => unsafe {
+                let old = c::set_curterm(ptr::mut_null());
+                if old != term {
+                    // somebody else's terminal was current, so leave it that
+                    // way
+                    c::set_curterm(old);
+                }
+                c::del_curterm(term);
+            },
+            FileSource(_) => (),
+        }
+    }
+}
+
+// the terminal used by the free functions in this module
+local_data_key!(default_info: TermInfo)
+
 /**
  * Initialize the terminfo database.
  *
- * This must be called before any functions from this module are used. The
- * current terminal is determined by looking at the `TERM` environment
- * variable.
+ * This loads the capabilities of the current terminal (see
+ * `TermInfo::from_env`) for use by the free functions in this module. It is
+ * done automatically the first time one of them is called.
  */
-pub fn init () {
-    unsafe { c::setupterm(ptr::null(), 1, ptr::null()) };
+pub fn init () -> InfoResult<()> {
+    let info = try!(TermInfo::from_env());
+    default_info.replace(Some(info));
+    Ok(())
+}
+
+fn with_default<T> (f: |&TermInfo| -> InfoResult<T>) -> InfoResult<T> {
+    if default_info.get().is_none() {
+        try!(init());
+    }
+    let info = default_info.get().unwrap();
+    f(&*info)
 }
 
 macro_rules! def_escape(
     ($name:ident -> $escape:expr) => (
-        pub fn $name () -> ~str {
+        pub fn $name () -> InfoResult<String> {
             let attr = $escape;
-            match escape(attr) {
-                Some(e) => e,
-                None    => fail!(format!("{:s} is not supported on this terminal",
-                                      attr)),
-            }
+            required(attr, escape(attr))
         }
     );
     ($name:ident -> $escape:expr, $ty1:ident) => (
-        pub fn $name (p1: $ty1) -> ~str {
+        pub fn $name (p1: $ty1) -> InfoResult<String> {
             let attr = $escape;
-            match escape1(attr, p1 as int) {
-                Some(e) => e,
-                None    => fail!(format!("{:s} is not supported on this terminal",
-                                      attr)),
-            }
+            required(attr, escape1(attr, p1 as int))
         }
     );
     ($name:ident -> $escape:expr, $ty1:ident, $ty2:ident) => (
-        pub fn $name (p1: $ty1, p2: $ty2) -> ~str {
+        pub fn $name (p1: $ty1, p2: $ty2) -> InfoResult<String> {
             let attr = $escape;
-            match escape2(attr, p1 as int, p2 as int) {
-                Some(e) => e,
-                None    => fail!(format!("{:s} is not supported on this terminal",
-                                      attr)),
-            }
+            required(attr, escape2(attr, p1 as int, p2 as int))
+        }
+    );
+)
+
+macro_rules! def_number(
+    ($name:ident -> $cap:expr) => (
+        pub fn $name () -> InfoResult<Option<uint>> {
+            with_default(|info| {
+                info.number($cap).map(|n| { n.map(|n| { n as uint }) })
+            })
+        }
+    );
+)
+
+macro_rules! def_flag(
+    ($name:ident -> $cap:expr) => (
+        pub fn $name () -> InfoResult<bool> {
+            with_default(|info| { info.flag($cap) })
         }
     );
 )
@@ -77,6 +399,33 @@ macro_rules! def_escape(
 // XXX macros can't take attributes yet (including documentation), so change
 // these to /// once that is fixed
 
+// The number of columns on the terminal.
+def_number!(columns            -> "cols")
+// The number of lines on the terminal.
+def_number!(lines              -> "lines")
+// The initial spacing of tab stops.
+def_number!(init_tabs          -> "it")
+// The maximum number of colors the terminal can display.
+def_number!(max_colors         -> "colors")
+// The maximum number of color pairs the terminal supports.
+def_number!(max_pairs          -> "pairs")
+
+// Whether the cursor wraps at the right margin.
+def_flag!(auto_right_margin    -> "am")
+// Whether a newline is ignored after wrapping at the margin.
+def_flag!(eat_newline_glitch   -> "xenl")
+// Whether erasing fills with the current background color.
+def_flag!(back_color_erase     -> "bce")
+// Whether it is safe to move the cursor in standout mode.
+def_flag!(move_standout_mode   -> "msgr")
+// Whether the terminal has a meta key.
+def_flag!(has_meta_key         -> "km")
+
+/// Whether the terminal supports direct (24-bit) colors.
+pub fn direct_color () -> InfoResult<bool> {
+    with_default(|info| { Ok(info.direct_color()) })
+}
+
 // The terminal escape to clear the screen.
 def_escape!(clear_screen         -> "clear")
 // The terminal escape to set the foreground color to `p1`.
@@ -174,26 +523,14 @@ def_escape!(key_f11         -> "kf11")
 def_escape!(key_f12         -> "kf12")
 
 /// The terminal escape generated by the F<`n`> key.
-pub fn key_f (n: uint) -> Box<str> {
-    let attr = format!("kf{:?}", n);
-    match escape(attr) {
-        Some(e) => e,
-        None    => fail!(format!("{:s} is not supported on this terminal", attr)),
-    }
+pub fn key_f (n: uint) -> InfoResult<String> {
+    let attr = format!("kf{:u}", n);
+    required(attr.as_slice(), escape(attr.as_slice()))
 }
 
 /// The terminal escape corresponding to the `name` terminfo capability.
-pub fn escape (name: &str) -> Option<Box<str>> {
-    unsafe {
-        let c_name =  name.to_c_str();
-        let e = tigetstr(c_name.unwrap());
-        if e == ptr::null() {
-            None
-        }
-        else {
-            Some(str::raw::from_c_str(e))
-        }
-    }
+pub fn escape (name: &str) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escape(name) })
 }
 
 /**
@@ -201,17 +538,8 @@ pub fn escape (name: &str) -> Option<Box<str>> {
  *
  * This capability must take one parameter, which should be passed as `p1`.
  */
-pub fn escape1 (name: &str, p1: int) -> Option<Box<str>> {
-    unsafe {
-        let c_name =  name.to_c_str();
-        let e = tigetstr(c_name.unwrap());
-        if e == ptr::null() {
-            None
-        }
-        else {
-            Some(str::raw::from_c_str(tparm1(e, p1)))
-        }
-    }
+pub fn escape1 (name: &str, p1: int) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escape1(name, p1) })
 }
 
 /**
@@ -220,42 +548,71 @@ pub fn escape1 (name: &str, p1: int) -> Option<Box<str>> {
  * This capability must take two parameters, which should be passed as `p1`
  * and `p2`.
  */
-pub fn escape2 (name: &str, p1: int, p2: int) -> Option<Box<str>> {
-    unsafe {
-        let c_name =  name.to_c_str();
-        let e = tigetstr(c_name.unwrap());
-        if e == ptr::null() {
-            None
-        }
-        else {
-            Some(str::raw::from_c_str(tparm2(e, p1, p2)))
-        }
-    }
+pub fn escape2 (name: &str, p1: int, p2: int) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escape2(name, p1, p2) })
 }
 
-unsafe fn tigetstr (name: *mut c_char) -> *mut c_char {
-    let c_out = c::tigetstr(name);
-    if c_out as int == -1 {
-        fail!(format!("{:s} is not a terminal capability",
-                   unsafe { str::raw::from_c_str(name) }));
+/**
+ * The terminal escape corresponding to the `name` terminfo capability.
+ *
+ * This capability can take up to nine parameters, which should be passed in
+ * `params`.
+ */
+pub fn escapen (name: &str, params: &[int]) -> InfoResult<Option<String>> {
+    with_default(|info| { info.escapen(name, params) })
+}
+
+/// The value of the boolean capability `name`.
+pub fn flag (name: &str) -> InfoResult<bool> {
+    with_default(|info| { info.flag(name) })
+}
+
+/// The value of the numeric capability `name`, if the terminal has it.
+pub fn number (name: &str) -> InfoResult<Option<int>> {
+    with_default(|info| { info.number(name) })
+}
+
+// turns a missing capability into an error, for the functions above which
+// are only useful if the terminal has it
+fn required (name: &str, escape: InfoResult<Option<String>>)
+             -> InfoResult<String> {
+    match try!(escape) {
+        Some(e) => Ok(e),
+        None    => Err(UnsupportedCapability(name.to_string())),
     }
-    c_out
 }
 
-unsafe fn tparm1 (name: *mut c_char, p1: int) -> *mut c_char {
-    let ret = c::tparm(name, p1 as c_long, 0, 0, 0, 0, 0, 0, 0, 0);
-    if ret == ptr::null() {
-        fail!(format!("Couldn't assemble parameters with {:s} {:d}",
-                   unsafe { str::raw::from_c_str(name) }, p1));
+// curses only looks at the global cur_term
+unsafe fn select (term: *mut c_void) {
+    c::set_curterm(term);
+}
+
+unsafe fn tigetstr (c_name: *const c_char, name: &str)
+                    -> InfoResult<*mut c_char> {
+    let c_out = c::tigetstr(c_name);
+    if c_out as int == -1 {
+        return Err(UnknownCapability(name.to_string()));
     }
-    ret
+    Ok(c_out)
 }
 
-unsafe fn tparm2 (name: *mut c_char, p1: int, p2: int) -> *mut c_char {
-    let ret = c::tparm(name, p1 as c_long, p2 as c_long, 0, 0, 0, 0, 0, 0, 0);
-    if ret == ptr::null() {
-        fail!(format!("Couldn't assemble parameters with {:s} {:d} {:d}",
-                   unsafe { str::raw::from_c_str(name) }, p1, p2));
+unsafe fn tparmn (cap: *mut c_char, name: &str, params: &[int])
+                  -> InfoResult<*mut c_char> {
+    if params.len() > 9 {
+        return Err(ParameterError(
+            format!("Too many parameters for {:s}", name)
+        ));
+    }
+    let mut p = [0 as c_long, ..9];
+    for (i, &param) in params.iter().enumerate() {
+        p[i] = param as c_long;
+    }
+    let ret = c::tparm(cap as *const c_char,
+                       p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7], p[8]);
+    if ret == ptr::mut_null() {
+        return Err(ParameterError(
+            format!("Couldn't assemble parameters with {:s} {}", name, params)
+        ));
     }
-    ret
+    Ok(ret)
 }
@@ -0,0 +1,48 @@
+use std::fmt;
+
+/// The ways in which looking up a terminal capability can fail.
+#[deriving(PartialEq, Eq, Clone)]
+pub enum Error {
+    /// The `TERM` environment variable isn't set.
+    NoTerminal,
+    /// No terminfo entry exists for the named terminal.
+    UnknownTerminal(String),
+    /// The named capability isn't a terminfo capability.
+    UnknownCapability(String),
+    /// The terminal doesn't have the named capability.
+    UnsupportedCapability(String),
+    /// A parameterized capability couldn't be expanded.
+    ParameterError(String),
+    /// The terminfo database couldn't be read.
+    DatabaseError(String),
+}
+
+impl fmt::Show for Error {
+    fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NoTerminal => {
+                write!(f, "The TERM environment variable is not set")
+            }
+            UnknownTerminal(ref name) => {
+                write!(f, "Unknown terminal type {:s}", name.as_slice())
+            }
+            UnknownCapability(ref name) => {
+                write!(f, "Unknown terminal capability {:s}", name.as_slice())
+            }
+            UnsupportedCapability(ref name) => {
+                write!(f, "{:s} is not supported on this terminal",
+                       name.as_slice())
+            }
+            ParameterError(ref msg) => {
+                write!(f, "Couldn't expand capability: {:s}", msg.as_slice())
+            }
+            DatabaseError(ref msg) => {
+                write!(f, "Couldn't read terminfo database: {:s}",
+                       msg.as_slice())
+            }
+        }
+    }
+}
+
+/// The result of a terminfo lookup.
+pub type InfoResult<T> = Result<T, Error>;
@@ -0,0 +1,560 @@
+use std::char;
+
+/// A parameter for a parameterized terminfo capability.
+#[deriving(Clone, PartialEq, Show)]
+pub enum Param {
+    Number(int),
+    Words(String),
+}
+
+/**
+ * The variables that can be set and read by a capability string.
+ *
+ * The static variables (`%PA` through `%PZ`) keep their values between
+ * calls to `expand` which are given the same `Variables`, while the dynamic
+ * variables (`%Pa` through `%Pz`) are reset each time.
+ */
+pub struct Variables {
+    sta: Vec<Param>,
+    dyn: Vec<Param>,
+}
+
+impl Variables {
+    pub fn new () -> Variables {
+        Variables {
+            sta: Vec::from_elem(26, Number(0)),
+            dyn: Vec::from_elem(26, Number(0)),
+        }
+    }
+
+    fn slot<'a> (&'a mut self, name: char) -> Result<&'a mut Param, String> {
+        match name {
+            'A'..'Z' => Ok(self.sta.get_mut(name as uint - 'A' as uint)),
+            'a'..'z' => Ok(self.dyn.get_mut(name as uint - 'a' as uint)),
+            _        => Err(format!("invalid variable name {:c}", name)),
+        }
+    }
+}
+
+#[deriving(Clone)]
+struct Flags {
+    left: bool,
+    sign: bool,
+    space: bool,
+    alternate: bool,
+    zero: bool,
+    width: uint,
+    precision: Option<uint>,
+}
+
+impl Flags {
+    fn new () -> Flags {
+        Flags {
+            left: false,
+            sign: false,
+            space: false,
+            alternate: false,
+            zero: false,
+            width: 0,
+            precision: None,
+        }
+    }
+}
+
+/**
+ * Expands the parameterized capability string `cap`.
+ *
+ * This implements the full parameter language described in terminfo(5):
+ * parameters (`%p1` through `%p9`), constants (`%{nn}` and `%'c'`),
+ * arithmetic, bitwise, logical and comparison operators, conditionals
+ * (`%?...%t...%e...%;`), variables, `%l`, `%i`, and printf-style output
+ * (`%d`, `%o`, `%x`, `%X`, `%s` with flags, width and precision, and `%c`).
+ * Parameters which aren't passed in are treated as 0.
+ */
+pub fn expand (cap: &str, params: &[Param],
+               vars: &mut Variables) -> Result<String, String> {
+    let chars: Vec<char> = cap.chars().collect();
+    let chars = chars.as_slice();
+
+    let mut params: Vec<Param> = params.iter().map(|p| { p.clone() }).collect();
+    if params.len() > 9 {
+        return Err("too many parameters".to_string());
+    }
+    while params.len() < 9 {
+        params.push(Number(0));
+    }
+
+    for v in vars.dyn.mut_iter() {
+        *v = Number(0);
+    }
+
+    let mut stack: Vec<Param> = vec!();
+    let mut out = String::new();
+    let mut i = 0u;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        if c != '%' {
+            out.push_char(c);
+            continue;
+        }
+
+        let op = try!(next_char(chars, &mut i));
+        match op {
+            '%' => out.push_char('%'),
+            'c' => {
+                // like tparm, a zero is sent as \200, so that it doesn't cut
+                // the string short
+                let n = match try!(pop_number(&mut stack)) {
+                    0 => 0x80,
+                    n => n,
+                };
+                match char::from_u32(n as u32) {
+                    Some(ch) => out.push_char(ch),
+                    None     => {
+                        return Err(format!("invalid character {:d}", n));
+                    }
+                }
+            }
+            'p' => {
+                let d = try!(next_char(chars, &mut i));
+                match d.to_digit(10) {
+                    Some(n) if n >= 1 => stack.push(params.get(n - 1).clone()),
+                    _                 => {
+                        return Err(format!("invalid parameter %p{:c}", d));
+                    }
+                }
+            }
+            'P' => {
+                let name = try!(next_char(chars, &mut i));
+                let val = try!(pop(&mut stack));
+                *try!(vars.slot(name)) = val;
+            }
+            'g' => {
+                let name = try!(next_char(chars, &mut i));
+                let val = try!(vars.slot(name)).clone();
+                stack.push(val);
+            }
+            '\'' => {
+                let ch = try!(next_char(chars, &mut i));
+                if try!(next_char(chars, &mut i)) != '\'' {
+                    return Err("unterminated character constant".to_string());
+                }
+                stack.push(Number(ch as int));
+            }
+            '{' => {
+                let mut n = 0i;
+                loop {
+                    let d = try!(next_char(chars, &mut i));
+                    if d == '}' {
+                        break;
+                    }
+                    match d.to_digit(10) {
+                        Some(v) => n = n * 10 + v as int,
+                        None    => {
+                            return Err(format!("invalid integer constant \
+                                                character {:c}", d));
+                        }
+                    }
+                }
+                stack.push(Number(n));
+            }
+            'l' => {
+                match try!(pop(&mut stack)) {
+                    Words(s)  => stack.push(Number(s.as_slice().char_len() as int)),
+                    Number(_) => {
+                        return Err("%l requires a string parameter".to_string());
+                    }
+                }
+            }
+            '+' | '-' | '*' | '/' | 'm' | '&' | '|' | '^'
+                | '=' | '<' | '>' | 'A' | 'O' => {
+                let b = try!(pop_number(&mut stack));
+                let a = try!(pop_number(&mut stack));
+                stack.push(Number(binop(op, a, b)));
+            }
+            '!' => {
+                let a = try!(pop_number(&mut stack));
+                stack.push(Number(if a == 0 { 1 } else { 0 }));
+            }
+            '~' => {
+                let a = try!(pop_number(&mut stack));
+                stack.push(Number(!a));
+            }
+            'i' => {
+                // only the first two parameters are incremented
+                for p in params.mut_slice(0, 2).mut_iter() {
+                    match *p {
+                        Number(ref mut n) => *n += 1,
+                        Words(_)          => (),
+                    }
+                }
+            }
+            '?' | ';' => (),
+            't' => {
+                if try!(pop_number(&mut stack)) == 0 {
+                    i = try!(skip_branch(chars, i, true));
+                }
+            }
+            'e' => {
+                // we only get here if the condition was true
+                i = try!(skip_branch(chars, i, false));
+            }
+            'd' | 'o' | 'x' | 'X' | 's' => {
+                let val = try!(pop(&mut stack));
+                out.push_str(try!(format(val, op, &Flags::new())).as_slice());
+            }
+            ':' | '#' | ' ' | '.' | '0'..'9' => {
+                i -= 1;
+                let (flags, conv) = try!(parse_flags(chars, &mut i));
+                let val = try!(pop(&mut stack));
+                out.push_str(try!(format(val, conv, &flags)).as_slice());
+            }
+            _ => {
+                return Err(format!("unknown escape sequence %{:c}", op));
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn next_char (chars: &[char], i: &mut uint) -> Result<char, String> {
+    if *i >= chars.len() {
+        return Err("unexpected end of capability string".to_string());
+    }
+    let c = chars[*i];
+    *i += 1;
+    Ok(c)
+}
+
+fn pop (stack: &mut Vec<Param>) -> Result<Param, String> {
+    match stack.pop() {
+        Some(p) => Ok(p),
+        None    => Err("parameter stack underflow".to_string()),
+    }
+}
+
+fn pop_number (stack: &mut Vec<Param>) -> Result<int, String> {
+    match try!(pop(stack)) {
+        Number(n) => Ok(n),
+        Words(_)  => Err("expected a number, found a string".to_string()),
+    }
+}
+
+fn binop (op: char, a: int, b: int) -> int {
+    match op {
+        '+' => a + b,
+        '-' => a - b,
+        '*' => a * b,
+        // ncurses gives 0 for division by zero, rather than failing
+        '/' => if b == 0 { 0 } else { a / b },
+        'm' => if b == 0 { 0 } else { a % b },
+        '&' => a & b,
+        '|' => a | b,
+        '^' => a ^ b,
+        '=' => (a == b) as int,
+        '<' => (a < b) as int,
+        '>' => (a > b) as int,
+        'A' => (a != 0 && b != 0) as int,
+        'O' => (a != 0 || b != 0) as int,
+        _   => unreachable!(),
+    }
+}
+
+// skips over the rest of the current branch of a conditional, returning the
+// position just after the %e (if `to_else` is set) or %; that ends it
+fn skip_branch (chars: &[char], start: uint,
+                to_else: bool) -> Result<uint, String> {
+    let mut i = start;
+    let mut level = 0u;
+    while i + 1 < chars.len() {
+        if chars[i] != '%' {
+            i += 1;
+            continue;
+        }
+        match chars[i + 1] {
+            '?'                            => level += 1,
+            ';' if level == 0              => return Ok(i + 2),
+            ';'                            => level -= 1,
+            'e' if level == 0 && to_else   => return Ok(i + 2),
+            _                              => (),
+        }
+        i += 2;
+    }
+    // a missing %; at the end of the string is allowed
+    Ok(chars.len())
+}
+
+// parses %[[:]flags][width[.precision]][doxXs], starting after the %
+fn parse_flags (chars: &[char], i: &mut uint) -> Result<(Flags, char), String> {
+    let mut flags = Flags::new();
+
+    if chars[*i] == ':' {
+        *i += 1;
+    }
+    loop {
+        match try!(next_char(chars, i)) {
+            '-' => flags.left = true,
+            '+' => flags.sign = true,
+            ' ' => flags.space = true,
+            '#' => flags.alternate = true,
+            _   => { *i -= 1; break }
+        }
+    }
+
+    if *i < chars.len() && chars[*i] == '0' {
+        flags.zero = true;
+    }
+    flags.width = parse_digits(chars, i);
+
+    if *i < chars.len() && chars[*i] == '.' {
+        *i += 1;
+        flags.precision = Some(parse_digits(chars, i));
+    }
+
+    match try!(next_char(chars, i)) {
+        c @ 'd' | c @ 'o' | c @ 'x' | c @ 'X' | c @ 's' => Ok((flags, c)),
+        c => Err(format!("invalid format conversion %{:c}", c)),
+    }
+}
+
+fn parse_digits (chars: &[char], i: &mut uint) -> uint {
+    let mut n = 0u;
+    while *i < chars.len() {
+        match chars[*i].to_digit(10) {
+            Some(d) => n = n * 10 + d,
+            None    => break,
+        }
+        *i += 1;
+    }
+    n
+}
+
+fn format (val: Param, conv: char, flags: &Flags) -> Result<String, String> {
+    let (prefix, body) = match (val, conv) {
+        (Number(n), 'd') => {
+            let mut digits = format!("{}", if n < 0 { -(n as i64) } else { n as i64 });
+            pad_precision(&mut digits, flags.precision);
+            let sign = if n < 0 {
+                "-"
+            }
+            else if flags.sign {
+                "+"
+            }
+            else if flags.space {
+                " "
+            }
+            else {
+                ""
+            };
+            (sign.to_string(), digits)
+        }
+        (Number(n), 'o') => {
+            let mut digits = format!("{:o}", n as u32);
+            pad_precision(&mut digits, flags.precision);
+            if flags.alternate && !digits.as_slice().starts_with("0") {
+                digits = format!("0{}", digits);
+            }
+            ("".to_string(), digits)
+        }
+        (Number(n), 'x') | (Number(n), 'X') => {
+            let mut digits = if conv == 'x' {
+                format!("{:x}", n as u32)
+            }
+            else {
+                format!("{:X}", n as u32)
+            };
+            pad_precision(&mut digits, flags.precision);
+            let prefix = if flags.alternate && n != 0 {
+                format!("0{:c}", conv)
+            }
+            else {
+                "".to_string()
+            };
+            (prefix, digits)
+        }
+        (Words(s), 's') => {
+            let s = match flags.precision {
+                Some(p) => s.as_slice().chars().take(p).collect(),
+                None    => s,
+            };
+            ("".to_string(), s)
+        }
+        (Words(_), _) => {
+            return Err(format!("%{:c} requires a number", conv));
+        }
+        (Number(_), _) => {
+            return Err(format!("%{:c} requires a string", conv));
+        }
+    };
+
+    let len = prefix.len() + body.len();
+    if len >= flags.width {
+        return Ok(format!("{}{}", prefix, body));
+    }
+
+    let padding = flags.width - len;
+    if flags.left {
+        Ok(format!("{}{}{}", prefix, body, " ".repeat(padding)))
+    }
+    else if flags.zero && flags.precision.is_none() && conv != 's' {
+        Ok(format!("{}{}{}", prefix, "0".repeat(padding), body))
+    }
+    else {
+        Ok(format!("{}{}{}", " ".repeat(padding), prefix, body))
+    }
+}
+
+fn pad_precision (digits: &mut String, precision: Option<uint>) {
+    match precision {
+        Some(p) if p > digits.len() => {
+            *digits = format!("{}{}", "0".repeat(p - digits.len()), digits);
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn test_cursor_address () {
+    check_expand("\x1b[%i%p1%d;%p2%dH", &[4, 9], "\x1b[5;10H");
+    check_expand("%i%p1%d %p2%d %p3%d", &[1, 2, 3], "2 3 3");
+    check_expand("%p1%d%%", &[50], "50%");
+}
+
+#[test]
+fn test_conditionals () {
+    // setaf from xterm-256color
+    let setaf = "\x1b[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d\
+                 %e38;5;%p1%d%;m";
+    check_expand(setaf, &[1], "\x1b[31m");
+    check_expand(setaf, &[10], "\x1b[92m");
+    check_expand(setaf, &[200], "\x1b[38;5;200m");
+
+    let chain = "%?%p1%{1}%=%tone%e%p1%{2}%=%ttwo%eother%;";
+    check_expand(chain, &[1], "one");
+    check_expand(chain, &[2], "two");
+    check_expand(chain, &[3], "other");
+
+    let nested = "%?%p1%t%?%p2%tab%ea%;%eb%;";
+    check_expand(nested, &[1, 1], "ab");
+    check_expand(nested, &[1, 0], "a");
+    check_expand(nested, &[0, 1], "b");
+}
+
+#[test]
+fn test_sgr () {
+    // sgr from xterm-256color
+    let sgr = "%?%p9%t\x1b(0%e\x1b(B%;\x1b[0%?%p6%t;1%;%?%p5%t;2%;\
+               %?%p2%t;4%;%?%p1%p3%|%t;7%;%?%p4%t;5%;%?%p7%t;8%;m";
+    check_expand(sgr, &[0, 0, 0, 0, 0, 0, 0, 0, 0], "\x1b(B\x1b[0m");
+    check_expand(sgr, &[1, 0, 0, 0, 0, 1, 0, 0, 0], "\x1b(B\x1b[0;1;7m");
+    check_expand(sgr, &[0, 1, 0, 0, 0, 0, 0, 0, 1], "\x1b(0\x1b[0;4m");
+    check_expand(sgr, &[0, 0, 1, 1, 1, 0, 1, 0, 0], "\x1b(B\x1b[0;2;7;5;8m");
+}
+
+#[test]
+fn test_arithmetic () {
+    check_expand("%{3}%{4}%*%d", &[], "12");
+    check_expand("%{7}%{2}%+%d", &[], "9");
+    check_expand("%{7}%{2}%-%d", &[], "5");
+    check_expand("%{7}%{2}%/%d", &[], "3");
+    check_expand("%{7}%{2}%m%d", &[], "1");
+    check_expand("%{7}%{0}%/%d", &[], "0");
+
+    check_expand("%{6}%{3}%&%d", &[], "2");
+    check_expand("%{6}%{3}%|%d", &[], "7");
+    check_expand("%{6}%{3}%^%d", &[], "5");
+    check_expand("%p1%~%d", &[0], "-1");
+    check_expand("%p1%!%d", &[0], "1");
+    check_expand("%p1%!%d", &[5], "0");
+
+    check_expand("%{1}%{0}%A%d", &[], "0");
+    check_expand("%{1}%{0}%O%d", &[], "1");
+    check_expand("%{1}%{2}%<%d", &[], "1");
+    check_expand("%{1}%{2}%>%d", &[], "0");
+    check_expand("%{2}%{2}%=%d", &[], "1");
+
+    check_expand("%'a'%d", &[], "97");
+    check_expand("%p1%c", &[65], "A");
+    check_expand("%p1%c", &[0], "\x80");
+
+    // initc from xterm-256color
+    check_expand("\x1b]4;%p1%d;rgb:%p2%{255}%*%{1000}%/%2.2X/\
+                  %p3%{255}%*%{1000}%/%2.2X/%p4%{255}%*%{1000}%/%2.2X\x1b\\",
+                 &[1, 1000, 500, 0], "\x1b]4;1;rgb:FF/7F/00\x1b\\");
+}
+
+#[test]
+fn test_formatting () {
+    check_expand("%p1%x", &[255], "ff");
+    check_expand("%p1%X", &[255], "FF");
+    check_expand("%p1%o", &[8], "10");
+    check_expand("%p1%x", &[-1], "ffffffff");
+    check_expand("%p1%d", &[-5], "-5");
+    check_expand("%p1%02d", &[5], "05");
+    check_expand("%p1%3d", &[5], "  5");
+    check_expand("%p1%:-3d|", &[5], "5  |");
+    check_expand("%p1%.3d", &[5], "005");
+    check_expand("%p1%5.3d", &[-5], " -005");
+    check_expand("%p1%#x", &[255], "0xff");
+    check_expand("%p1%#o", &[8], "010");
+    check_expand("%p1% d", &[5], " 5");
+}
+
+#[test]
+fn test_strings () {
+    let params = [Words("hello".to_string())];
+    let params = params.as_slice();
+    check_expand_params("%p1%s", params, "hello");
+    check_expand_params("%p1%l%d", params, "5");
+    check_expand_params("%p1%:-7s|", params, "hello  |");
+    check_expand_params("%p1%7s|", params, "  hello|");
+    check_expand_params("%p1%.2s", params, "he");
+    assert!(expand("%p1%d", params, &mut Variables::new()).is_err());
+    assert!(expand("%p1%s", &[Number(1)], &mut Variables::new()).is_err());
+}
+
+#[test]
+fn test_variables () {
+    check_expand("%p1%Pa%ga%ga%+%d", &[3], "6");
+
+    let mut vars = Variables::new();
+    assert!(expand("%p1%PZ%p1%Pz", &[Number(3)], &mut vars).is_ok());
+    assert!(expand("%gZ%d", &[], &mut vars) == Ok("3".to_string()));
+    assert!(expand("%gz%d", &[], &mut vars) == Ok("0".to_string()));
+}
+
+#[test]
+fn test_errors () {
+    let mut vars = Variables::new();
+    assert!(expand("%z", &[], &mut vars).is_err());
+    assert!(expand("%d", &[], &mut vars).is_err());
+    assert!(expand("%{12", &[], &mut vars).is_err());
+    assert!(expand("%p0%d", &[], &mut vars).is_err());
+    assert!(expand("%'a", &[], &mut vars).is_err());
+    assert!(expand("%", &[], &mut vars).is_err());
+    assert!(expand("%p1%P!", &[], &mut vars).is_err());
+}
+
+#[cfg(test)]
+fn check_expand (cap: &str, params: &[int], expected: &str) {
+    let params: Vec<Param> = params.iter().map(|&n| { Number(n) }).collect();
+    check_expand_params(cap, params.as_slice(), expected);
+}
+
+#[cfg(test)]
+fn check_expand_params (cap: &str, params: &[Param], expected: &str) {
+    match expand(cap, params, &mut Variables::new()) {
+        Ok(s)  => {
+            if s.as_slice() != expected {
+                fail!(format!("expanding {}: expected {}, got {}",
+                              cap.escape_default(),
+                              expected.escape_default(),
+                              s.as_slice().escape_default()));
+            }
+        }
+        Err(e) => fail!(format!("expanding {}: {}", cap.escape_default(), e)),
+    }
+}
@@ -1,51 +1,468 @@
+#include <errno.h>
 #include <fcntl.h>
+#include <poll.h>
+#include <signal.h>
 #include <stdlib.h>
+#include <string.h>
+#include <termios.h>
+#include <time.h>
+#include <unistd.h>
 #include <sys/select.h>
 
-/* XXX XXX XXX - major hack - most of this C stuff should go away anyway */
-int tty_fd = -1;
+/* the SIGWINCH handler writes a byte here, so that select can notice it */
+static int winch_pipe[2] = { -1, -1 };
+/* and for SIGTSTP, which gets handled outside of the signal handler */
+static int tstp_pipe[2] = { -1, -1 };
 
-static int get_tty_fd(void)
+static void winch_handler(int sig)
 {
-    if (tty_fd < 0)
-        tty_fd = open("/dev/tty", O_RDONLY);
+    int saved_errno = errno;
+    char byte = 0;
 
-    return tty_fd;
+    (void)sig;
+    write(winch_pipe[1], &byte, 1);
+    errno = saved_errno;
 }
-/* end hack */
 
-int timed_read(int timeout)
+static void tstp_handler(int sig)
+{
+    int saved_errno = errno;
+    char byte = 0;
+
+    (void)sig;
+    write(tstp_pipe[1], &byte, 1);
+    errno = saved_errno;
+}
+
+static int set_flags(int fd)
+{
+    int flags = fcntl(fd, F_GETFL);
+
+    if (flags == -1 || fcntl(fd, F_SETFL, flags | O_NONBLOCK) == -1) {
+        return -1;
+    }
+
+    return fcntl(fd, F_SETFD, FD_CLOEXEC);
+}
+
+static int make_pipe(int fds[2])
+{
+    if (pipe(fds) == -1) {
+        return -1;
+    }
+
+    if (set_flags(fds[0]) == -1 || set_flags(fds[1]) == -1) {
+        return -1;
+    }
+
+    return 0;
+}
+
+static void drain(int fd)
 {
     char byte;
 
-    if (timeout >= 0) {
-        fd_set readfds;
-        struct timeval t;
+    while (read(fd, &byte, 1) == 1)
+        ;
+}
+
+int watch_winch()
+{
+    struct sigaction sa;
+
+    if (winch_pipe[0] >= 0) {
+        return 0;
+    }
+
+    if (make_pipe(winch_pipe) == -1) {
+        return errno;
+    }
+
+    sa.sa_handler = winch_handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = SA_RESTART;
+
+    return sigaction(SIGWINCH, &sa, NULL) == 0 ? 0 : errno;
+}
+
+/* how many callers want SIGTSTP watched, and what it did before that */
+static int tstp_watchers = 0;
+static struct sigaction old_tstp;
+static int tstp_caught = 0;
+
+/* makes SIGTSTP get reported by wait_input instead of stopping the process,
+ * or puts back what it did before once every enable has been matched by a
+ * disable. if it was being ignored, it is left that way. */
+int watch_tstp(int enable)
+{
+    struct sigaction sa;
+
+    if (!enable) {
+        if (tstp_watchers == 0 || --tstp_watchers > 0) {
+            return 0;
+        }
+        if (tstp_caught) {
+            sigaction(SIGTSTP, &old_tstp, NULL);
+            tstp_caught = 0;
+        }
+        drain(tstp_pipe[0]);
+        return 0;
+    }
+
+    /* failures still count, so that the disables match up */
+    if (tstp_watchers++ > 0) {
+        return 0;
+    }
+
+    if (tstp_pipe[0] < 0 && make_pipe(tstp_pipe) == -1) {
+        return errno;
+    }
+
+    if (sigaction(SIGTSTP, NULL, &old_tstp) == -1) {
+        return errno;
+    }
+    /* a shell without job control ignores it for us */
+    if (old_tstp.sa_handler == SIG_IGN) {
+        return 0;
+    }
+
+    sa.sa_handler = tstp_handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = SA_RESTART;
+
+    if (sigaction(SIGTSTP, &sa, NULL) == -1) {
+        return errno;
+    }
+    tstp_caught = 1;
+
+    return 0;
+}
+
+/* stops the process group like ^Z does, returning once it is continued */
+void suspend_self()
+{
+    struct sigaction sa, old;
 
+    sa.sa_handler = SIG_DFL;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+
+    sigaction(SIGTSTP, &sa, &old);
+    kill(0, SIGTSTP);
+    sigaction(SIGTSTP, &old, NULL);
+
+    /* forget about any ^Z that came in meanwhile */
+    if (tstp_pipe[0] >= 0)
+        drain(tstp_pipe[0]);
+}
+
+/* returns a byte from fd, -1 on timeout or eof, or -2 if the window was
+ * resized (which is only looked at if resize is set) */
+int timed_read(int fd, int timeout, int resize)
+{
+    unsigned char byte;
+    fd_set readfds;
+    struct timeval t;
+    int winch, nfds, ret;
+
+    winch = resize ? winch_pipe[0] : -1;
+    nfds = fd;
+    if (winch > nfds)
+        nfds = winch;
+
+    do {
         FD_ZERO(&readfds);
-        FD_SET(get_tty_fd(), &readfds);
+        FD_SET(fd, &readfds);
+        if (winch >= 0)
+            FD_SET(winch, &readfds);
 
         t.tv_sec  = timeout / 1000000;
         t.tv_usec = timeout % 1000000;
 
-        if (select(get_tty_fd() + 1, &readfds, NULL, NULL, &t) == 1) {
-            if (read(get_tty_fd(), &byte, 1) == 1) {
-                return byte;
-            }
-            else {
-                return -1;
-            }
-        }
-        else {
-            return -1;
-        }
+        ret = select(nfds + 1, &readfds, NULL, NULL, timeout >= 0 ? &t : NULL);
+    } while (ret == -1 && errno == EINTR);
+
+    if (ret <= 0) {
+        return -1;
+    }
+
+    if (winch >= 0 && FD_ISSET(winch, &readfds)) {
+        /* several signals only need one resize */
+        drain(winch);
+        return -2;
+    }
+
+    if (read(fd, &byte, 1) == 1) {
+        return byte;
     }
     else {
-        if (read(get_tty_fd(), &byte, 1) == 1) {
-            return byte;
+        return -1;
+    }
+}
+
+/* like timed_read without a timeout, but doesn't get interrupted by resizes */
+int read_byte(int fd)
+{
+    unsigned char byte;
+    int ret;
+
+    do {
+        ret = read(fd, &byte, 1);
+    } while (ret == -1 && errno == EINTR);
+
+    return ret == 1 ? byte : -1;
+}
+
+/* returns 0, or errno if not everything could be written */
+int write_all(int fd, const char *buf, size_t len)
+{
+    ssize_t ret;
+
+    while (len > 0) {
+        ret = write(fd, buf, len);
+        if (ret == -1) {
+            if (errno == EINTR)
+                continue;
+            return errno;
         }
-        else {
-            return -1;
+        buf += ret;
+        len -= ret;
+    }
+
+    return 0;
+}
+
+void close_fd(int fd)
+{
+    close(fd);
+}
+
+/* makes a pipe for wakeup, which can be written to from any thread.
+ * returns 0, or errno if it couldn't be made. */
+int open_wakeup(int fds[2])
+{
+    int saved_errno;
+
+    if (pipe(fds) == -1) {
+        return errno;
+    }
+
+    if (set_flags(fds[0]) == -1 || set_flags(fds[1]) == -1) {
+        saved_errno = errno;
+        close(fds[0]);
+        close(fds[1]);
+        return saved_errno;
+    }
+
+    return 0;
+}
+
+void wakeup(int fd)
+{
+    char byte = 0;
+
+    write(fd, &byte, 1);
+}
+
+/* milliseconds since some arbitrary point, which doesn't jump around */
+long long now_ms()
+{
+    struct timespec ts;
+
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+    return (long long)ts.tv_sec * 1000 + ts.tv_nsec / 1000000;
+}
+
+#define WAIT_TTY     0
+#define WAIT_RESIZE  1
+#define WAIT_WAKEUP  2
+#define WAIT_FD      3
+#define WAIT_TIMEOUT 4
+#define WAIT_SUSPEND 5
+
+/* waits for tty, the SIGWINCH and SIGTSTP pipes (if signals is set), the
+ * wake_fd pipe, or any of the nfds descriptors in fds to become readable, for
+ * at most timeout milliseconds (or forever if it is negative). returns one of
+ * the WAIT_ constants, and for WAIT_FD, stores the descriptor in *ready. on
+ * failure, returns -1 with errno set. */
+int wait_input(int tty, int wake_fd, int signals, const int *fds, int nfds,
+               int timeout, int *ready)
+{
+    struct pollfd *p;
+    int i, ret;
+
+    p = malloc((nfds + 4) * sizeof(struct pollfd));
+    if (p == NULL) {
+        return -1;
+    }
+
+    /* poll skips negative descriptors */
+    p[0].fd = tty;
+    p[1].fd = signals ? winch_pipe[0] : -1;
+    p[2].fd = wake_fd;
+    p[3].fd = signals ? tstp_pipe[0] : -1;
+    for (i = 0; i < nfds; ++i) {
+        p[i + 4].fd = fds[i];
+    }
+    for (i = 0; i < nfds + 4; ++i) {
+        p[i].events = POLLIN;
+        p[i].revents = 0;
+    }
+
+    ret = poll(p, nfds + 4, timeout);
+    if (ret == -1) {
+        /* a signal interrupted us, so let the caller look at the timers
+         * again before waiting some more */
+        ret = errno == EINTR ? WAIT_TIMEOUT : -1;
+    }
+    else if (ret == 0) {
+        ret = WAIT_TIMEOUT;
+    }
+    else if (p[1].revents) {
+        drain(winch_pipe[0]);
+        ret = WAIT_RESIZE;
+    }
+    else if (p[3].revents) {
+        drain(tstp_pipe[0]);
+        ret = WAIT_SUSPEND;
+    }
+    else if (p[0].revents) {
+        ret = WAIT_TTY;
+    }
+    else if (p[2].revents) {
+        drain(wake_fd);
+        ret = WAIT_WAKEUP;
+    }
+    else {
+        for (i = 0; i < nfds; ++i) {
+            if (p[i + 4].revents) {
+                *ready = fds[i];
+                break;
+            }
         }
+        ret = WAIT_FD;
     }
+
+    free(p);
+    return ret;
+}
+
+/* what gets done to the terminal before dying from one of these signals */
+static const int fatal_signals[] = { SIGTERM, SIGINT, SIGHUP, SIGQUIT };
+#define NFATAL (sizeof(fatal_signals) / sizeof(fatal_signals[0]))
+static struct sigaction old_actions[NFATAL];
+/* which of them fatal_handler was installed for */
+static int caught[NFATAL];
+static int catching_fatal = 0;
+
+static int restore_fd = -1;
+static char restore_seq[512];
+static size_t restore_len = 0;
+static struct termios restore_termios;
+static int restore_has_termios = 0;
+
+static void fatal_handler(int sig)
+{
+    size_t i;
+
+    if (restore_fd >= 0) {
+        write(restore_fd, restore_seq, restore_len);
+        if (restore_has_termios)
+            tcsetattr(restore_fd, TCSANOW, &restore_termios);
+    }
+
+    /* let whatever was there before deal with the signal, once this handler
+     * returns and it gets unblocked */
+    for (i = 0; i < NFATAL; ++i) {
+        if (fatal_signals[i] == sig)
+            sigaction(sig, &old_actions[i], NULL);
+    }
+    raise(sig);
+}
+
+static void block_fatal(int how, sigset_t *old)
+{
+    sigset_t set;
+    size_t i;
+
+    sigemptyset(&set);
+    for (i = 0; i < NFATAL; ++i) {
+        sigaddset(&set, fatal_signals[i]);
+    }
+    sigprocmask(how, &set, old);
+}
+
+/* sets what the fatal signal handler writes to fd (truncated to fit), and
+ * the termios settings it restores, if t isn't NULL */
+void set_restore(int fd, const char *seq, size_t len, const struct termios *t)
+{
+    sigset_t old;
+
+    if (len > sizeof(restore_seq))
+        len = sizeof(restore_seq);
+
+    block_fatal(SIG_BLOCK, &old);
+    restore_fd = fd;
+    memcpy(restore_seq, seq, len);
+    restore_len = len;
+    restore_has_termios = t != NULL;
+    if (t != NULL)
+        restore_termios = *t;
+    sigprocmask(SIG_SETMASK, &old, NULL);
+}
+
+/* puts back the handlers that catch_fatal replaced */
+static void uncatch_fatal(void)
+{
+    size_t i;
+
+    for (i = 0; i < NFATAL; ++i) {
+        if (caught[i])
+            sigaction(fatal_signals[i], &old_actions[i], NULL);
+        caught[i] = 0;
+    }
+}
+
+/* installs or removes the fatal signal handler */
+int catch_fatal(int enable)
+{
+    struct sigaction sa;
+    size_t i;
+    int saved_errno;
+
+    if (enable == catching_fatal) {
+        return 0;
+    }
+
+    if (!enable) {
+        uncatch_fatal();
+        catching_fatal = 0;
+        return 0;
+    }
+
+    sa.sa_handler = fatal_handler;
+    sigemptyset(&sa.sa_mask);
+    sa.sa_flags = 0;
+
+    for (i = 0; i < NFATAL; ++i) {
+        if (sigaction(fatal_signals[i], NULL, &old_actions[i]) == -1)
+            break;
+        /* an ignored signal doesn't kill the process, and should stay
+         * ignored (like SIGINT and SIGQUIT for background jobs) */
+        if (old_actions[i].sa_handler == SIG_IGN)
+            continue;
+        if (sigaction(fatal_signals[i], &sa, NULL) == -1)
+            break;
+        caught[i] = 1;
+    }
+
+    /* don't leave some of them caught and not others */
+    if (i < NFATAL) {
+        saved_errno = errno;
+        uncatch_fatal();
+        return saved_errno;
+    }
+    catching_fatal = 1;
+
+    return 0;
 }
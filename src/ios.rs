@@ -1,77 +1,593 @@
+use std::c_str::ToCStr;
 use std::finally::Finally;
+use std::io::{IoError, IoResult, InvalidInput, IoUnavailable};
+use std::{os, ptr};
+
+#[cfg(test)]
+use util;
 
 
 mod c {
+    use super::Termios;
+
 #[link(name = "termios_wrapper")]
     extern {
-        pub fn cooked () -> i32;
-        pub fn cbreak () -> i32;
-        pub fn raw () -> i32;
-        pub fn echo (enable: i32) -> i32;
+        pub fn open_tty () -> i32;
+
+        pub fn size(fd: i32, cols: *mut u32, rows: *mut u32) -> i32;
+        pub fn set_size(fd: i32, cols: u32, rows: u32) -> i32;
+
+        pub fn open_pty (master: *mut i32, slave: *mut i32) -> i32;
+        pub fn spawn_pty (argv: *const *const i8, cols: u32, rows: u32,
+                          master: *mut i32, pid: *mut i32) -> i32;
+        pub fn wait_child (pid: i32) -> i32;
+    }
+
+    // these come from libc itself
+    extern {
+        pub fn tcgetattr (fd: i32, t: *mut Termios) -> i32;
+        pub fn tcsetattr (fd: i32, action: i32, t: *const Termios) -> i32;
+        pub fn tcdrain (fd: i32) -> i32;
+        pub fn tcflush (fd: i32, queue: i32) -> i32;
+        pub fn tcflow (fd: i32, action: i32) -> i32;
+        pub fn tcsendbreak (fd: i32, duration: i32) -> i32;
+        pub fn cfgetispeed (t: *const Termios) -> u32;
+        pub fn cfgetospeed (t: *const Termios) -> u32;
+        pub fn cfsetispeed (t: *mut Termios, speed: u32) -> i32;
+        pub fn cfsetospeed (t: *mut Termios, speed: u32) -> i32;
+    }
+}
+
+// the values here are the ones linux uses
+
+bitflags!(
+    flags InputFlags: u32 {
+        static IGNBRK  = 0o000001,
+        static BRKINT  = 0o000002,
+        static IGNPAR  = 0o000004,
+        static PARMRK  = 0o000010,
+        static INPCK   = 0o000020,
+        static ISTRIP  = 0o000040,
+        static INLCR   = 0o000100,
+        static IGNCR   = 0o000200,
+        static ICRNL   = 0o000400,
+        static IUCLC   = 0o001000,
+        static IXON    = 0o002000,
+        static IXANY   = 0o004000,
+        static IXOFF   = 0o010000,
+        static IMAXBEL = 0o020000,
+        static IUTF8   = 0o040000
+    }
+)
+
+bitflags!(
+    flags OutputFlags: u32 {
+        static OPOST  = 0o000001,
+        static OLCUC  = 0o000002,
+        static ONLCR  = 0o000004,
+        static OCRNL  = 0o000010,
+        static ONOCR  = 0o000020,
+        static ONLRET = 0o000040,
+        static OFILL  = 0o000100,
+        static OFDEL  = 0o000200,
+        static NLDLY  = 0o000400,
+        static CRDLY  = 0o003000,
+        static TABDLY = 0o014000,
+        static BSDLY  = 0o020000,
+        static VTDLY  = 0o040000,
+        static FFDLY  = 0o100000
+    }
+)
+
+bitflags!(
+    flags ControlFlags: u32 {
+        // the baud rate is kept in here too, but should be changed with
+        // the speed methods of `Termios`
+        static CBAUD   = 0o010017,
+        static CSIZE   = 0o000060,
+        static CS5     = 0o000000,
+        static CS6     = 0o000020,
+        static CS7     = 0o000040,
+        static CS8     = 0o000060,
+        static CSTOPB  = 0o000100,
+        static CREAD   = 0o000200,
+        static PARENB  = 0o000400,
+        static PARODD  = 0o001000,
+        static HUPCL   = 0o002000,
+        static CLOCAL  = 0o004000,
+        static CRTSCTS = 0o20000000000
+    }
+)
+
+bitflags!(
+    flags LocalFlags: u32 {
+        static ISIG    = 0o000001,
+        static ICANON  = 0o000002,
+        static XCASE   = 0o000004,
+        static ECHO    = 0o000010,
+        static ECHOE   = 0o000020,
+        static ECHOK   = 0o000040,
+        static ECHONL  = 0o000100,
+        static NOFLSH  = 0o000200,
+        static TOSTOP  = 0o000400,
+        static ECHOCTL = 0o001000,
+        static ECHOPRT = 0o002000,
+        static ECHOKE  = 0o004000,
+        static FLUSHO  = 0o010000,
+        static PENDIN  = 0o040000,
+        static IEXTEN  = 0o100000,
+        static EXTPROC = 0o200000
+    }
+)
+
+/// The number of control characters in `Termios::cc`.
+pub static NCCS: uint = 32;
+
+// indexes into `Termios::cc`
+pub static VINTR: uint    = 0;
+pub static VQUIT: uint    = 1;
+pub static VERASE: uint   = 2;
+pub static VKILL: uint    = 3;
+pub static VEOF: uint     = 4;
+pub static VTIME: uint    = 5;
+pub static VMIN: uint     = 6;
+pub static VSWTC: uint    = 7;
+pub static VSTART: uint   = 8;
+pub static VSTOP: uint    = 9;
+pub static VSUSP: uint    = 10;
+pub static VEOL: uint     = 11;
+pub static VREPRINT: uint = 12;
+pub static VDISCARD: uint = 13;
+pub static VWERASE: uint  = 14;
+pub static VLNEXT: uint   = 15;
+pub static VEOL2: uint    = 16;
+
+// baud rates, and the values used for them in termios
+static Speeds: [(uint, u32), ..19] = [
+    (0, 0), (50, 1), (75, 2), (110, 3), (134, 4), (150, 5), (200, 6),
+    (300, 7), (600, 8), (1200, 9), (1800, 10), (2400, 11), (4800, 12),
+    (9600, 13), (19200, 14), (38400, 15), (57600, 0o010001),
+    (115200, 0o010002), (230400, 0o010003),
+];
+
+/**
+ * The settings of a terminal, as read by `tcgetattr`.
+ *
+ * This is laid out like `struct termios` in glibc, so that it can be passed
+ * to the C functions directly. Other systems lay it out differently, so
+ * there, the functions which take one fail instead.
+ */
+#[repr(C)]
+pub struct Termios {
+    pub iflag: InputFlags,
+    pub oflag: OutputFlags,
+    pub cflag: ControlFlags,
+    pub lflag: LocalFlags,
+    line: u8,
+    /// The control characters, indexed by `VINTR`, `VMIN` and so on.
+    pub cc: [u8, ..NCCS],
+    ispeed: u32,
+    ospeed: u32,
+}
+
+impl Termios {
+    /**
+     * Changes the settings like `cfmakeraw` does.
+     *
+     * Input is available a byte at a time, without echo, signals, or any
+     * translation of input or output, and characters are eight bits.
+     */
+    pub fn make_raw (&mut self) {
+        self.iflag.remove(IGNBRK | BRKINT | PARMRK | ISTRIP | INLCR | IGNCR
+                          | ICRNL | IXON);
+        self.oflag.remove(OPOST);
+        self.lflag.remove(ECHO | ECHONL | ICANON | ISIG | IEXTEN);
+        self.cflag.remove(CSIZE | PARENB);
+        self.cflag.insert(CS8);
+        self.cc[VMIN] = 1;
+        self.cc[VTIME] = 0;
+    }
+
+    /// The input baud rate.
+    pub fn input_speed (&self) -> uint {
+        from_speed(unsafe { c::cfgetispeed(self) })
+    }
+
+    /// The output baud rate.
+    pub fn output_speed (&self) -> uint {
+        from_speed(unsafe { c::cfgetospeed(self) })
+    }
+
+    /**
+     * Changes the input baud rate.
+     *
+     * Only the standard rates up to 230400 can be used.
+     */
+    pub fn set_input_speed (&mut self, baud: uint) -> IoResult<()> {
+        try!(check_layout());
+        let speed = try!(to_speed(baud));
+        check(unsafe { c::cfsetispeed(self, speed) })
+    }
+
+    /**
+     * Changes the output baud rate.
+     *
+     * Only the standard rates up to 230400 can be used.
+     */
+    pub fn set_output_speed (&mut self, baud: uint) -> IoResult<()> {
+        try!(check_layout());
+        let speed = try!(to_speed(baud));
+        check(unsafe { c::cfsetospeed(self, speed) })
+    }
+}
+
+// the layout of Termios and the values of the flags are only right on linux
+// with the generic termios definitions (mips, powerpc and sparc have their
+// own), and passing it to anything else would scribble over memory
+#[cfg(all(target_os = "linux",
+          any(target_arch = "x86", target_arch = "x86_64",
+              target_arch = "arm", target_arch = "aarch64")))]
+fn check_layout () -> IoResult<()> {
+    Ok(())
+}
+
+#[cfg(not(all(target_os = "linux",
+              any(target_arch = "x86", target_arch = "x86_64",
+                  target_arch = "arm", target_arch = "aarch64"))))]
+fn check_layout () -> IoResult<()> {
+    Err(IoError {
+        kind: IoUnavailable,
+        desc: "termios settings are only supported on linux on x86 and arm",
+        detail: None,
+    })
+}
+
+fn from_speed (speed: u32) -> uint {
+    for &(baud, s) in Speeds.iter() {
+        if s == speed {
+            return baud;
+        }
+    }
+    0
+}
+
+fn to_speed (baud: uint) -> IoResult<u32> {
+    for &(b, speed) in Speeds.iter() {
+        if b == baud {
+            return Ok(speed);
+        }
+    }
+    Err(IoError {
+        kind: InvalidInput,
+        desc: "unsupported baud rate",
+        detail: Some(format!("{}", baud)),
+    })
+}
+
+// turns the return value of a libc function into a result
+fn check (ret: i32) -> IoResult<()> {
+    if ret == -1 {
+        Err(IoError::last_error())
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// When `tcsetattr` applies the new settings.
+pub enum SetAction {
+    /// Right away.
+    SetNow = 0,
+    /// After the output that has been written is sent.
+    SetDrain = 1,
+    /// Like `SetDrain`, but also discarding input that hasn't been read.
+    SetFlush = 2,
+}
+
+/// Which data `tcflush` discards.
+pub enum Queue {
+    /// Input that hasn't been read.
+    InputQueue = 0,
+    /// Output that hasn't been sent.
+    OutputQueue = 1,
+    /// Both of them.
+    BothQueues = 2,
+}
+
+/// What `tcflow` does.
+pub enum FlowAction {
+    /// Stops sending output.
+    SuspendOutput = 0,
+    /// Starts sending output again.
+    ResumeOutput = 1,
+    /// Sends a STOP character, asking the terminal to stop sending input.
+    SuspendInput = 2,
+    /// Sends a START character, asking the terminal to send input again.
+    ResumeInput = 3,
+}
+
+/// Gets the settings of the terminal `fd`.
+pub fn tcgetattr (fd: int) -> IoResult<Termios> {
+    try!(check_layout());
+    let mut t = Termios {
+        iflag: InputFlags::empty(),
+        oflag: OutputFlags::empty(),
+        cflag: ControlFlags::empty(),
+        lflag: LocalFlags::empty(),
+        line: 0,
+        cc: [0, ..NCCS],
+        ispeed: 0,
+        ospeed: 0,
+    };
+    try!(check(unsafe { c::tcgetattr(fd as i32, &mut t) }));
+    Ok(t)
+}
 
-        pub fn get() -> uint;
-        pub fn set(t: uint);
+/// Changes the settings of the terminal `fd`.
+pub fn tcsetattr (fd: int, action: SetAction, t: &Termios) -> IoResult<()> {
+    try!(check_layout());
+    check(unsafe { c::tcsetattr(fd as i32, action as i32, t) })
+}
+
+/// Waits until all output written to `fd` has been sent.
+pub fn tcdrain (fd: int) -> IoResult<()> {
+    check(unsafe { c::tcdrain(fd as i32) })
+}
+
+/// Discards data which hasn't been read or sent yet.
+pub fn tcflush (fd: int, queue: Queue) -> IoResult<()> {
+    check(unsafe { c::tcflush(fd as i32, queue as i32) })
+}
+
+/// Suspends or resumes the flow of data.
+pub fn tcflow (fd: int, action: FlowAction) -> IoResult<()> {
+    check(unsafe { c::tcflow(fd as i32, action as i32) })
+}
+
+/**
+ * Sends a break, which is a stream of zero bits.
+ *
+ * A `duration` of 0 sends it for somewhere between a quarter and half a
+ * second, and other values have a meaning which depends on the system.
+ */
+pub fn tcsendbreak (fd: int, duration: int) -> IoResult<()> {
+    check(unsafe { c::tcsendbreak(fd as i32, duration as i32) })
+}
 
-        pub fn size(cols: *mut u32, rows: *mut u32);
+/**
+ * Open the controlling terminal of the process.
+ *
+ * The other functions here take the returned descriptor (or any other one
+ * referring to a terminal, like stdin or a pty). It is up to the caller to
+ * close it.
+ */
+pub fn open_tty () -> IoResult<int> {
+    let fd = unsafe { c::open_tty() };
+    if fd < 0 {
+        Err(IoError::last_error())
+    }
+    else {
+        Ok(fd as int)
+    }
+}
+
+// changes the settings of `fd` with `f`, returning 0 or errno
+fn modify (fd: int, f: |&mut Termios|) -> int {
+    let mut t = match tcgetattr(fd) {
+        Ok(t)  => t,
+        Err(_) => return os::errno() as int,
+    };
+    f(&mut t);
+    match tcsetattr(fd, SetNow, &t) {
+        Ok(()) => 0,
+        Err(_) => os::errno() as int,
     }
 }
 
 /**
- * Put the terminal into cooked mode.
+ * Put the terminal `fd` into cooked mode.
  *
  * This is the normal line-buffered mode.
  */
-pub fn cooked () -> int {
-    unsafe { c::cooked() as int }
+pub fn cooked (fd: int) -> int {
+    modify(fd, |t| {
+        t.lflag.insert(ICANON | ISIG | IEXTEN);
+        t.iflag.insert(IXON | BRKINT);
+    })
 }
 
 /**
- * Put the terminal into cbreak mode.
+ * Put the terminal `fd` into cbreak mode.
  *
  * This is the normal unbuffered mode.
  */
-pub fn cbreak () -> int {
-    unsafe { c::cbreak() as int }
+pub fn cbreak (fd: int) -> int {
+    modify(fd, |t| {
+        t.lflag.insert(ISIG);
+        t.lflag.remove(ICANON | IEXTEN);
+        t.iflag.insert(IXON | BRKINT);
+    })
 }
 
 /**
- * Put the terminal into raw mode.
+ * Put the terminal `fd` into raw mode.
  *
  * This is like cbreak mode, except that control characters (like ^C) are not
- * translated into signals.
+ * translated into signals, and nothing is done to the input and output, so
+ * newlines have to be written as "\r\n". See `Termios::make_raw`.
  */
-pub fn raw () -> int {
-    unsafe { c::raw() as int }
+pub fn raw (fd: int) -> int {
+    modify(fd, |t| { t.make_raw() })
 }
 
 /**
- * Change the echo mode of the terminal.
+ * Change the echo mode of the terminal `fd`.
  *
  * `true` turns echo on, and `false` turns echo off.
  */
-pub fn echo (enable: bool) -> int {
-    unsafe { c::echo(enable as i32) as int }
+pub fn echo (fd: int, enable: bool) -> int {
+    modify(fd, |t| {
+        if enable {
+            t.lflag.insert(ECHO);
+        }
+        else {
+            t.lflag.remove(ECHO);
+        }
+    })
 }
 
 /**
- * Run a block of code, restoring the terminal state when the block ends.
+ * Run a block of code, restoring the state of the terminal `fd` when the
+ * block ends.
  *
  * This will ensure you don't leave the terminal in a broken state, even if
  * the current task fails.
  */
-pub fn preserve<T> (mut body: || -> T) -> T {
-    let orig = unsafe { c::get() };
+pub fn preserve<T> (fd: int, mut body: || -> T) -> T {
+    let orig = tcgetattr(fd);
     body.finally(|| {
-        unsafe { c::set(orig) };
+        match orig {
+            Ok(ref t) => { let _ = tcsetattr(fd, SetNow, t); }
+            Err(_)    => (),
+        }
     })
 }
 
-/// Returns the size of the terminal, as `(columns, rows)`.
-pub fn size() -> (uint, uint) {
+/**
+ * Returns the size of the terminal `fd`, as `(columns, rows)`.
+ *
+ * This is `(0, 0)` if `fd` isn't a terminal.
+ */
+pub fn size(fd: int) -> (uint, uint) {
     let mut cols: u32 = 0;
     let mut rows: u32 = 0;
     unsafe {
-        c::size(&mut cols, &mut rows)
+        c::size(fd as i32, &mut cols, &mut rows);
     }
     (cols as uint, rows as uint)
 }
+
+/**
+ * Change the size of the terminal `fd`.
+ *
+ * This sends a `SIGWINCH` to the processes running in it.
+ */
+pub fn set_size (fd: int, cols: uint, rows: uint) -> int {
+    unsafe { c::set_size(fd as i32, cols as u32, rows as u32) as int }
+}
+
+/**
+ * Open a new pseudo-terminal, returning `(master, slave)`.
+ *
+ * Whatever is written to the master can be read from the slave, as if it
+ * was typed, and the other way around. Both are closed when the process
+ * execs something, and it is up to the caller to close them otherwise.
+ */
+pub fn open_pty () -> IoResult<(int, int)> {
+    let mut master = -1i32;
+    let mut slave = -1i32;
+    if unsafe { c::open_pty(&mut master, &mut slave) } != 0 {
+        return Err(IoError::last_error());
+    }
+    Ok((master as int, slave as int))
+}
+
+/**
+ * Run `program` in a new pseudo-terminal of `cols` by `rows`, returning
+ * `(master, pid)`.
+ *
+ * `program` is looked up in `$PATH`, and gets `args` as its arguments. It
+ * runs in a new session, with the slave as its controlling terminal and
+ * standard input and output, so talking to it is done by reading and
+ * writing the master. Use `wait_child` to get its exit status.
+ */
+pub fn spawn (program: &str, args: &[&str], cols: uint, rows: uint)
+             -> IoResult<(int, int)> {
+    let mut c_args = vec!(program.to_c_str());
+    for arg in args.iter() {
+        c_args.push(arg.to_c_str());
+    }
+    let mut argv: Vec<*const i8> = c_args.iter().map(|arg| {
+        arg.as_ptr()
+    }).collect();
+    argv.push(ptr::null());
+
+    let mut master = -1i32;
+    let mut pid = -1i32;
+    let ret = unsafe {
+        c::spawn_pty(argv.as_ptr(), cols as u32, rows as u32,
+                     &mut master, &mut pid)
+    };
+    if ret != 0 {
+        return Err(IoError::last_error());
+    }
+    Ok((master as int, pid as int))
+}
+
+/**
+ * Wait for the child process `pid` to exit.
+ *
+ * Returns its exit status, or 128 plus the number of the signal that
+ * killed it, like the shell does.
+ */
+pub fn wait_child (pid: int) -> IoResult<int> {
+    match unsafe { c::wait_child(pid as i32) } {
+        -1     => Err(IoError::last_error()),
+        status => Ok(status as int),
+    }
+}
+
+#[test]
+fn test_pty () {
+    let (master, slave) = open_pty().unwrap();
+    set_size(master, 30, 10);
+    assert!(size(slave) == (30, 10));
+    util::close(master);
+    util::close(slave);
+
+    let (master, pid) = spawn("sh", ["-c", "stty size; echo hi"], 40, 12)
+        .unwrap();
+    let mut output = String::new();
+    loop {
+        match util::timed_read(master, 1000000, false) {
+            util::InputChar(c) => output.push_char(c),
+            _                  => break,
+        }
+    }
+    util::close(master);
+    assert!(output.as_slice() == "12 40\r\nhi\r\n");
+    assert!(wait_child(pid) == Ok(0));
+
+    assert!(spawn("/nonexistent", [], 80, 24).is_err());
+}
+
+#[test]
+fn test_termios () {
+    let (master, slave) = open_pty().unwrap();
+
+    let mut t = tcgetattr(slave).unwrap();
+    assert!(t.lflag.contains(ICANON));
+    t.make_raw();
+    t.cc[VMIN] = 0;
+    t.cc[VTIME] = 5;
+    t.set_input_speed(9600).unwrap();
+    t.set_output_speed(9600).unwrap();
+    assert!(t.set_output_speed(12345).is_err());
+    tcsetattr(slave, SetNow, &t).unwrap();
+
+    let t = tcgetattr(slave).unwrap();
+    assert!(!t.lflag.intersects(ICANON | ECHO | ISIG));
+    assert!(!t.oflag.contains(OPOST));
+    assert!(!t.iflag.contains(ICRNL));
+    assert!(t.cflag.contains(CS8));
+    assert!(t.cc[VMIN] == 0 && t.cc[VTIME] == 5);
+    assert!(t.input_speed() == 9600 && t.output_speed() == 9600);
+
+    assert!(cooked(slave) == 0);
+    assert!(tcgetattr(slave).unwrap().lflag.contains(ICANON | ISIG));
+    tcflush(slave, BothQueues).unwrap();
+    tcdrain(slave).unwrap();
+
+    util::close(master);
+    util::close(slave);
+}
@@ -0,0 +1,294 @@
+use hexes::{Term, Attrs};
+use std::{cmp, iter, str};
+
+// runs of changed cells closer together than this are redrawn as one,
+// since rewriting a few unchanged cells is cheaper than moving the cursor
+static MaxGap: uint = 4;
+
+/// A character on the screen, along with the attributes it is drawn with.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub struct Cell {
+    pub ch: char,
+    pub attrs: Attrs,
+}
+
+impl Cell {
+    /// A space with the default attributes.
+    pub fn blank () -> Cell {
+        Cell { ch: ' ', attrs: Attrs::new() }
+    }
+}
+
+/**
+ * A double-buffered model of the screen.
+ *
+ * Drawing happens in a back buffer of cells, and `present` updates the
+ * terminal to match it, only sending the parts that changed since the last
+ * `present`. This is like `wnoutrefresh` and `doupdate` in curses.
+ */
+pub struct Screen {
+    cols: uint,
+    rows: uint,
+    // what we think is on the terminal
+    front: Vec<Cell>,
+    // what is being drawn
+    back: Vec<Cell>,
+    // whether the terminal needs to be cleared and redrawn from scratch
+    invalid: bool,
+    cursor: Option<(uint, uint)>,
+}
+
+impl Screen {
+    /// Creates a blank screen with `cols` columns and `rows` rows.
+    pub fn new (cols: uint, rows: uint) -> Screen {
+        Screen {
+            cols: cols,
+            rows: rows,
+            front: Vec::from_elem(cols * rows, Cell::blank()),
+            back: Vec::from_elem(cols * rows, Cell::blank()),
+            invalid: true,
+            cursor: None,
+        }
+    }
+
+    /// The size of the screen, as `(columns, rows)`.
+    pub fn size (&self) -> (uint, uint) {
+        (self.cols, self.rows)
+    }
+
+    /**
+     * Changes the size of the screen, for instance after a `ResizeEvent`.
+     *
+     * The contents of the back buffer are kept where they still fit, and the
+     * next `present` redraws everything.
+     */
+    pub fn resize (&mut self, cols: uint, rows: uint) {
+        let mut back = Vec::from_elem(cols * rows, Cell::blank());
+        for row in iter::range(0, cmp::min(rows, self.rows)) {
+            for col in iter::range(0, cmp::min(cols, self.cols)) {
+                *back.get_mut(row * cols + col) =
+                    self.back.get(row * self.cols + col).clone();
+            }
+        }
+        self.cols = cols;
+        self.rows = rows;
+        self.back = back;
+        self.front = Vec::from_elem(cols * rows, Cell::blank());
+        self.invalid = true;
+    }
+
+    /**
+     * Forgets what is on the terminal, so that the next `present` redraws
+     * everything.
+     *
+     * This is needed if something else has written to the terminal.
+     */
+    pub fn invalidate (&mut self) {
+        self.invalid = true;
+    }
+
+    /// Blanks out the back buffer.
+    pub fn clear (&mut self) {
+        for cell in self.back.mut_iter() {
+            *cell = Cell::blank();
+        }
+    }
+
+    /// The cell at (`col`, `row`) in the back buffer.
+    pub fn get<'a> (&'a self, col: uint, row: uint) -> &'a Cell {
+        assert!(col < self.cols && row < self.rows);
+        self.back.get(row * self.cols + col)
+    }
+
+    /// Draws `ch` at (`col`, `row`) in the back buffer.
+    pub fn set (&mut self, col: uint, row: uint, ch: char, attrs: &Attrs) {
+        assert!(col < self.cols && row < self.rows);
+        *self.back.get_mut(row * self.cols + col) =
+            Cell { ch: ch, attrs: attrs.clone() };
+    }
+
+    /**
+     * Draws `text` starting at (`col`, `row`) in the back buffer.
+     *
+     * Anything past the end of the row is cut off.
+     */
+    pub fn write (&mut self, col: uint, row: uint, text: &str, attrs: &Attrs) {
+        for (i, ch) in text.chars().enumerate() {
+            if col + i >= self.cols {
+                break;
+            }
+            self.set(col + i, row, ch, attrs);
+        }
+    }
+
+    /**
+     * Sets where the cursor should be left after `present`.
+     *
+     * With `None`, it is left wherever drawing ended up.
+     */
+    pub fn set_cursor (&mut self, pos: Option<(uint, uint)>) {
+        self.cursor = pos;
+    }
+
+    /// Updates the terminal to show the contents of the back buffer.
+    pub fn present (&mut self, term: &mut Term) {
+        // the terminal cursor position, if we know it
+        let mut pos = None;
+        if self.invalid {
+            term.reset_attributes();
+            term.clear();
+            for cell in self.front.mut_iter() {
+                *cell = Cell::blank();
+            }
+            self.invalid = false;
+            pos = Some((0u, 0u));
+        }
+
+        // writing to the last cell would scroll the screen on terminals that
+        // wrap immediately, so it keeps whatever it had before
+        let skip_last = term.info().auto_right_margin()
+            && !term.info().eat_newline_glitch();
+        let stale = match self.front.last() {
+            Some(cell) if skip_last => Some(cell.clone()),
+            _                       => None,
+        };
+
+        for row in iter::range(0, self.rows) {
+            let start = row * self.cols;
+            let end = start + self.cols;
+            let runs = changed_runs(self.front.slice(start, end),
+                                    self.back.slice(start, end));
+            for &(from, to) in runs.iter() {
+                if pos != Some((from, row)) {
+                    // the cells in between can be written again to move
+                    // over them, if they look the same as what we'd write
+                    let gap = match pos {
+                        Some((col, r)) if r == row && col < from => {
+                            self.gap_text(start + col, start + from,
+                                          &term.attrs())
+                        }
+                        _                                        => None,
+                    };
+                    let gap = gap.as_ref().map(|s| { s.as_slice() });
+                    term.move_cursor_over(from, row, gap);
+                }
+                for col in iter::range(from, to) {
+                    if skip_last && row == self.rows - 1
+                        && col == self.cols - 1 {
+                        break;
+                    }
+                    let cell = self.back.get(start + col);
+                    term.set_attrs(&cell.attrs);
+                    term.write(str::from_char(cell.ch).as_slice());
+                }
+                // the cursor position is unclear once it reaches the margin
+                pos = if to < self.cols { Some((to, row)) } else { None };
+            }
+        }
+        self.front = self.back.clone();
+        match stale {
+            Some(cell) => {
+                let last = self.front.len() - 1;
+                *self.front.get_mut(last) = cell;
+            }
+            None       => (),
+        }
+
+        match self.cursor {
+            Some((col, row)) => term.move_cursor(col, row),
+            None             => (),
+        }
+        term.flush();
+    }
+
+    // the characters in back[from, to), if they are all drawn with `attrs`
+    fn gap_text (&self, from: uint, to: uint, attrs: &Attrs) -> Option<String> {
+        let mut text = String::new();
+        for cell in self.back.slice(from, to).iter() {
+            if cell.attrs != *attrs {
+                return None;
+            }
+            text.push_char(cell.ch);
+        }
+        Some(text)
+    }
+}
+
+// the [from, to) ranges of cells in a row which need to be redrawn
+fn changed_runs (front: &[Cell], back: &[Cell]) -> Vec<(uint, uint)> {
+    let mut runs: Vec<(uint, uint)> = vec!();
+    for (i, (f, b)) in front.iter().zip(back.iter()).enumerate() {
+        if f == b {
+            continue;
+        }
+        match runs.pop() {
+            Some((from, to)) if i - to < MaxGap => runs.push((from, i + 1)),
+            Some(run)                           => {
+                runs.push(run);
+                runs.push((i, i + 1));
+            }
+            None                                => runs.push((i, i + 1)),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+fn row (s: &str) -> Vec<Cell> {
+    s.chars().map(|c| { Cell { ch: c, attrs: Attrs::new() } }).collect()
+}
+
+#[test]
+fn test_changed_runs () {
+    let front = row("hello world");
+    assert!(changed_runs(front.as_slice(), front.as_slice()) == vec!());
+    assert!(changed_runs(front.as_slice(), row("jello world").as_slice())
+            == vec!((0, 1)));
+    assert!(changed_runs(front.as_slice(), row("hello worle").as_slice())
+            == vec!((10, 11)));
+
+    // nearby changes are merged, and far away ones aren't
+    assert!(changed_runs(front.as_slice(), row("HeLlo world").as_slice())
+            == vec!((0, 3)));
+    assert!(changed_runs(front.as_slice(), row("Hello worlD").as_slice())
+            == vec!((0, 1), (10, 11)));
+
+    let mut bold = row("hello world");
+    bold.get_mut(6).attrs.bold = true;
+    assert!(changed_runs(front.as_slice(), bold.as_slice()) == vec!((6, 7)));
+}
+
+#[test]
+fn test_skip_last_cell () {
+    use std::io::{MemReader, MemWriter};
+    use fixtures::AnsiEntry;
+    use info::TermInfo;
+
+    // ansi has am without xenl, so the bottom right cell can't be drawn
+    let ansi = TermInfo::from_bytes(AnsiEntry.as_slice()).unwrap();
+    let mut term = Term::with_streams(
+        ansi,
+        box MemReader::new(vec!()) as Box<Reader + Send>,
+        box MemWriter::new() as Box<Writer + Send>
+    ).unwrap();
+    let mut screen = Screen::new(4, 2);
+    screen.write(0, 1, "abcd", &Attrs::new());
+    screen.present(&mut term);
+
+    // so it's still blank as far as the next present is concerned
+    assert!(screen.front.get(6).ch == 'c');
+    assert!(screen.front.get(7) == &Cell::blank());
+}
+
+#[test]
+fn test_resize () {
+    let mut screen = Screen::new(4, 2);
+    screen.write(1, 1, "abcdef", &Attrs::new());
+    assert!(screen.get(3, 1).ch == 'c');
+
+    screen.resize(3, 3);
+    assert!(screen.size() == (3, 3));
+    assert!(screen.get(1, 1).ch == 'a');
+    assert!(screen.get(2, 1).ch == 'b');
+    assert!(screen.get(2, 2) == &Cell::blank());
+}
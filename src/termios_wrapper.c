@@ -1,111 +1,156 @@
+/* for posix_openpt and friends */
+#define _GNU_SOURCE
+
 #include <fcntl.h>
 #include <stdlib.h>
 #include <errno.h>
 #include <termios.h>
+#include <unistd.h>
 #include <sys/ioctl.h>
+#include <sys/wait.h>
 
-/* very simplistic, ignores a lot of the settings that i don't understand,
- * patches welcome */
-
-/* XXX XXX XXX - major hack - most of this C stuff should go away anyway */
-extern int tty_fd;
-
-static int get_tty_fd(void)
+int open_tty()
 {
-    if (tty_fd < 0)
-        tty_fd = open("/dev/tty", O_RDONLY);
-
-    return tty_fd;
+    return open("/dev/tty", O_RDWR | O_NOCTTY | O_CLOEXEC);
 }
-/* end hack */
 
-int cooked()
+int size(int fd, unsigned int *cols, unsigned int *rows)
 {
-    struct termios t;
+    struct winsize ws;
 
-    if (tcgetattr(get_tty_fd(), &t) == -1) {
+    if (ioctl(fd, TIOCGWINSZ, &ws) == -1) {
+        *cols = 0;
+        *rows = 0;
         return errno;
     }
 
-    t.c_lflag |= (ICANON | ISIG | IEXTEN);
-    t.c_iflag |= (IXON | BRKINT);
-
-    return tcsetattr(get_tty_fd(), TCSANOW, &t) == 0 ? 0 : errno;
+    *cols = ws.ws_col;
+    *rows = ws.ws_row;
+    return 0;
 }
 
-int cbreak()
+int set_size(int fd, unsigned int cols, unsigned int rows)
 {
-    struct termios t;
+    struct winsize ws = { 0 };
 
-    if (tcgetattr(get_tty_fd(), &t) == -1) {
-        return errno;
-    }
+    ws.ws_col = cols;
+    ws.ws_row = rows;
 
-    t.c_lflag |= ISIG;
-    t.c_lflag &= ~(ICANON | IEXTEN);
-    t.c_iflag |= (IXON | BRKINT);
-
-    return tcsetattr(get_tty_fd(), TCSANOW, &t) == 0 ? 0 : errno;
+    return ioctl(fd, TIOCSWINSZ, &ws) == 0 ? 0 : errno;
 }
 
-int raw()
+static int set_cloexec(int fd)
 {
-    struct termios t;
-
-    if (tcgetattr(get_tty_fd(), &t) == -1) {
-        return errno;
-    }
+    return fcntl(fd, F_SETFD, FD_CLOEXEC);
+}
 
-    t.c_lflag &= ~(ICANON | ISIG | IEXTEN);
-    t.c_iflag &= ~(IXON | BRKINT);
+/* returns 0 or errno, like the rest of these */
+static int close_with_errno(int fd)
+{
+    int err = errno;
 
-    return tcsetattr(get_tty_fd(), TCSANOW, &t) == 0 ? 0 : errno;
+    close(fd);
+    errno = err;
+    return err;
 }
 
-int echo(int enabled)
+int open_pty(int *master, int *slave)
 {
-    struct termios t;
+    char *name;
 
-    if (tcgetattr(get_tty_fd(), &t) == -1) {
+    *master = posix_openpt(O_RDWR | O_NOCTTY);
+    if (*master == -1) {
         return errno;
     }
 
-    if (enabled) {
-        t.c_lflag |= ECHO;
+    if (set_cloexec(*master) == -1 || grantpt(*master) == -1
+        || unlockpt(*master) == -1 || (name = ptsname(*master)) == NULL) {
+        return close_with_errno(*master);
     }
-    else {
-        t.c_lflag &= ~ECHO;
+
+    *slave = open(name, O_RDWR | O_NOCTTY | O_CLOEXEC);
+    if (*slave == -1) {
+        return close_with_errno(*master);
     }
 
-    return tcsetattr(get_tty_fd(), TCSANOW, &t) == 0 ? 0 : errno;
+    return 0;
 }
 
-struct termios *get()
+/* runs argv[0] (looked up in $PATH) in a new session, whose controlling
+ * terminal is a new cols x rows pty. stores the master side of the pty and
+ * the pid of the child. */
+int spawn_pty(char *const *argv, unsigned int cols, unsigned int rows,
+              int *master, int *pid)
 {
-    struct termios *t;
+    int slave, err, status[2];
+    ssize_t n;
 
-    t = malloc(sizeof(struct termios));
-    if (tcgetattr(get_tty_fd(), t) == -1) {
-        return NULL;
+    err = open_pty(master, &slave);
+    if (err) {
+        return err;
     }
+    set_size(slave, cols, rows);
 
-    return t;
-}
+    /* the child sends errno through this if exec fails, and it gets closed
+     * without anything being written if exec works */
+    if (pipe(status) == -1) {
+        close(slave);
+        return close_with_errno(*master);
+    }
+    set_cloexec(status[0]);
+    set_cloexec(status[1]);
+
+    *pid = fork();
+    if (*pid == -1) {
+        close(slave);
+        close(status[0]);
+        close(status[1]);
+        return close_with_errno(*master);
+    }
 
-void set(struct termios *t)
-{
-    if (t == NULL) {
-        return;
+    if (*pid == 0) {
+        if (setsid() != -1 && ioctl(slave, TIOCSCTTY, 0) != -1
+            && dup2(slave, 0) != -1 && dup2(slave, 1) != -1
+            && dup2(slave, 2) != -1) {
+            execvp(argv[0], argv);
+        }
+        err = errno;
+        write(status[1], &err, sizeof(err));
+        _exit(127);
+    }
+
+    close(slave);
+    close(status[1]);
+    do {
+        n = read(status[0], &err, sizeof(err));
+    } while (n == -1 && errno == EINTR);
+    close(status[0]);
+
+    if (n == sizeof(err)) {
+        waitpid(*pid, NULL, 0);
+        close(*master);
+        errno = err;
+        return err;
     }
 
-    tcsetattr(get_tty_fd(), TCSANOW, t);
-    free(t);
+    return 0;
 }
 
-void size(unsigned int *cols, unsigned int *rows)
+/* waits for the child pid to exit, and returns its exit status (or 128 plus
+ * the signal that killed it), or -1 on error */
+int wait_child(int pid)
 {
-    struct winsize ws;
-    ioctl(get_tty_fd(), TIOCGWINSZ, &ws);
-    *cols = ws.ws_col;
-    *rows = ws.ws_row;
+    int status;
+
+    while (waitpid(pid, &status, 0) == -1) {
+        if (errno != EINTR) {
+            return -1;
+        }
+    }
+
+    if (WIFSIGNALED(status)) {
+        return 128 + WTERMSIG(status);
+    }
+
+    return WEXITSTATUS(status);
 }
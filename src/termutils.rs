@@ -3,8 +3,14 @@
 #![crate_type = "lib"]
 #![no_main]
 
+#[cfg(curses)]
+extern crate libc;
+
+pub mod color;
 pub mod hexes;
 pub mod ios;
+pub mod screen;
+pub mod vt;
 
 #[cfg(curses)]
 #[path = "info/curses.rs"]
@@ -14,5 +20,7 @@ pub mod info;
 #[path = "info/builtin.rs"]
 pub mod info;
 
+#[cfg(test)]
+mod fixtures;
 mod trie;
 mod util;
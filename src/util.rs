@@ -1,34 +1,269 @@
 use std::str;
 use std::uint;
 use std::iter;
+use std::io::{IoError, IoResult};
+use std::ptr;
+
+use ios;
+
+/// The result of `timed_read`.
+pub enum Input {
+    /// A character was read.
+    InputChar(char),
+    /// The terminal was resized while waiting.
+    InputResize,
+    /// The timeout expired, or the end of the input was reached.
+    InputNone,
+}
+
+/**
+ * Install a SIGWINCH handler, so that `timed_read` can report resizes.
+ *
+ * The handler only writes to a pipe that `timed_read` also waits on, so
+ * nothing unsafe happens in the signal handler itself.
+ */
+pub fn watch_resize () {
+    unsafe { io_helper::watch_winch(); }
+}
+
+/// The result of `wait`.
+pub enum Wait {
+    /// The terminal has input to read.
+    WaitTty,
+    /// The terminal was resized.
+    WaitResize,
+    /// `wakeup` was called.
+    WaitWakeup,
+    /// The given descriptor has input to read.
+    WaitFd(int),
+    /// The timeout expired (or a signal arrived).
+    WaitTimeout,
+    /// SIGTSTP was received, while `watch_suspend` is on.
+    WaitSuspend,
+}
+
+/**
+ * Make SIGTSTP get reported by `wait` instead of stopping the process, or
+ * put it back to normal.
+ *
+ * These nest, so it only goes back to what it did before once every enable
+ * has been matched by a disable. If it was being ignored, it stays ignored.
+ */
+pub fn watch_suspend (enable: bool) {
+    unsafe { io_helper::watch_tstp(enable as i32); }
+}
+
+/// Stop the process like ^Z does, returning once it is continued.
+pub fn suspend () {
+    unsafe { io_helper::suspend_self(); }
+}
+
+/**
+ * Create a pipe for `wakeup`, returning its read end (to pass to `wait`) and
+ * its write end.
+ */
+pub fn open_wakeup () -> IoResult<(int, int)> {
+    let mut fds = [-1i32, -1];
+    if unsafe { io_helper::open_wakeup(fds.as_mut_ptr()) } == 0 {
+        Ok((fds[0] as int, fds[1] as int))
+    }
+    else {
+        Err(IoError::last_error())
+    }
+}
+
+/// Interrupt a `wait` on the other end of the pipe `fd`, from any task.
+pub fn wakeup (fd: int) {
+    unsafe { io_helper::wakeup(fd as i32); }
+}
+
+/// A monotonic clock, in milliseconds.
+pub fn now () -> u64 {
+    unsafe { io_helper::now_ms() as u64 }
+}
+
+/**
+ * Wait for input on `tty` or any of `fds`, a wakeup through the pipe
+ * `wakeup` (see `open_wakeup`), or if `signals` is set, a resize or a
+ * suspend.
+ *
+ * `timeout` is in milliseconds, and a negative one waits forever. Being
+ * interrupted by a signal counts as a timeout.
+ */
+pub fn wait (tty: int, wakeup: int, signals: bool, fds: &[int],
+             timeout: int) -> IoResult<Wait> {
+    let fds: Vec<i32> = fds.iter().map(|&fd| { fd as i32 }).collect();
+    let mut ready = -1i32;
+    let ret = unsafe {
+        io_helper::wait_input(tty as i32, wakeup as i32, signals as i32,
+                              fds.as_ptr(), fds.len() as i32, timeout as i32,
+                              &mut ready)
+    };
+    match ret {
+        0 => Ok(WaitTty),
+        1 => Ok(WaitResize),
+        2 => Ok(WaitWakeup),
+        3 => Ok(WaitFd(ready as int)),
+        4 => Ok(WaitTimeout),
+        5 => Ok(WaitSuspend),
+        _ => Err(IoError::last_error()),
+    }
+}
 
 // XXX huge hack until there's a better built-in way to do this
 // can't use core::pipes::select or core::comm::selecti because there's no
 // way to get a background task to quit if it's blocking on an io call
 // this will need to wait on the real libuv bindings
-pub fn timed_read (timeout: int) -> Option<char> {
-    let first = unsafe { io_helper::timed_read(timeout as i32) };
+pub fn timed_read (fd: int, timeout: int, resize: bool) -> Input {
+    match timed_read_byte(fd, timeout, resize) {
+        InputChar(c) => decode(c as u8, || {
+            unsafe { io_helper::read_byte(fd as i32) as int }
+        }),
+        i            => i,
+    }
+}
+
+/**
+ * Like `timed_read`, but reads a single byte without decoding it as utf-8,
+ * for input which isn't text.
+ */
+pub fn timed_read_byte (fd: int, timeout: int, resize: bool) -> Input {
+    let first = unsafe {
+        io_helper::timed_read(fd as i32, timeout as i32, resize as i32)
+    };
+    if first == -2 {
+        return InputResize;
+    }
     if first < 0 {
-        return None;
+        return InputNone;
     }
+    InputChar(first as u8 as char)
+}
 
-    let mut buf = vec!(first as u8);
-    let nbytes = str::utf8_char_width(first as u8);
+/**
+ * Read a character from `reader`, which can't time out or be interrupted by
+ * a resize.
+ */
+pub fn stream_read (reader: &mut Reader) -> Input {
+    match stream_read_byte(reader) {
+        InputChar(c) => decode(c as u8, || {
+            match reader.read_byte() {
+                Ok(b)  => b as int,
+                Err(_) => -1,
+            }
+        }),
+        i            => i,
+    }
+}
+
+/// Like `stream_read`, but without decoding utf-8 (see `timed_read_byte`).
+pub fn stream_read_byte (reader: &mut Reader) -> Input {
+    match reader.read_byte() {
+        Ok(b)  => InputChar(b as char),
+        Err(_) => InputNone,
+    }
+}
+
+// reads the rest of the utf-8 character starting with `first`, where `next`
+// returns a byte, or something negative at the end of the input
+fn decode (first: u8, next: || -> int) -> Input {
+    let mut buf = vec!(first);
+    let nbytes = str::utf8_char_width(first);
+    // bytes which can't start a utf-8 character are passed through as they
+    // are
+    if nbytes <= 1 {
+        return InputChar(first as char);
+    }
 
     for _ in iter::range(0, nbytes - 1) {
-        let next = unsafe { io_helper::timed_read(-1 as i32) };
-        if next < 0 {
-            return None;
+        let b = next();
+        if b < 0 {
+            return InputNone;
+        }
+        buf.push(b as u8);
+    }
+
+    match str::from_utf8(buf.as_slice()) {
+        Some(s) => InputChar(s.char_at(0)),
+        None    => InputChar(first as char),
+    }
+}
+
+/// Writes to a file descriptor, which is left open when this is dropped.
+pub struct FdWriter {
+    fd: int,
+}
+
+impl FdWriter {
+    pub fn new (fd: int) -> FdWriter {
+        FdWriter { fd: fd }
+    }
+}
+
+impl Writer for FdWriter {
+    fn write (&mut self, buf: &[u8]) -> IoResult<()> {
+        let ret = unsafe {
+            io_helper::write_all(self.fd as i32, buf.as_ptr(), buf.len())
+        };
+        if ret == 0 {
+            Ok(())
+        }
+        else {
+            Err(IoError::last_error())
         }
-        buf.push(next as u8);
     }
+}
 
-    Some(unsafe { str::raw::from_byte(buf[0]) }.as_slice().char_at(0))
+/**
+ * Set what is done to the terminal `fd` if the process is killed by a signal
+ * while `catch_fatal` is on.
+ *
+ * `seq` is written to it, and its settings are changed to `termios`.
+ */
+pub fn set_restore (fd: int, seq: &str, termios: Option<&ios::Termios>) {
+    let t = match termios {
+        Some(t) => t as *const ios::Termios,
+        None    => ptr::null(),
+    };
+    unsafe {
+        io_helper::set_restore(fd as i32, seq.as_ptr(), seq.len(), t);
+    }
+}
+
+/**
+ * Install or remove handlers for SIGTERM, SIGINT, SIGHUP and SIGQUIT, which
+ * restore the terminal as set by `set_restore`, and then let the signal do
+ * what it would have done otherwise. Signals which are ignored stay that way.
+ */
+pub fn catch_fatal (enable: bool) {
+    unsafe { io_helper::catch_fatal(enable as i32); }
+}
+
+/// Closes a file descriptor.
+pub fn close (fd: int) {
+    unsafe { io_helper::close_fd(fd as i32); }
 }
 
 mod io_helper {
+    use ios;
+
 #[link(name = "io_helper")]
     extern {
-        pub fn timed_read (timeout: i32) -> i32;
+        pub fn timed_read (fd: i32, timeout: i32, resize: i32) -> i32;
+        pub fn read_byte (fd: i32) -> i32;
+        pub fn write_all (fd: i32, buf: *const u8, len: uint) -> i32;
+        pub fn close_fd (fd: i32);
+        pub fn set_restore (fd: i32, seq: *const u8, len: uint,
+                            t: *const ios::Termios);
+        pub fn catch_fatal (enable: i32) -> i32;
+        pub fn watch_winch () -> i32;
+        pub fn watch_tstp (enable: i32) -> i32;
+        pub fn suspend_self ();
+        pub fn open_wakeup (fds: *mut i32) -> i32;
+        pub fn wakeup (fd: i32);
+        pub fn now_ms () -> i64;
+        pub fn wait_input (tty: i32, wakeup: i32, signals: i32,
+                           fds: *const i32, nfds: i32, timeout: i32,
+                           ready: *mut i32) -> i32;
     }
 }
@@ -0,0 +1,849 @@
+use color;
+use color::{AnsiColor, BrightColor, DefaultColor, RgbColor};
+use color::Color;
+use hexes::Attrs;
+use hexes::{SingleUnderline, DoubleUnderline, CurlyUnderline};
+use hexes::{DottedUnderline, DashedUnderline, UnderlineStyle};
+#[cfg(test)] use info;
+use screen::Cell;
+use std::{cmp, iter, mem};
+
+/**
+ * A virtual terminal, for testing without a real one.
+ *
+ * This understands the subset of xterm (and the linux console) that the
+ * terminfo entries for them use, and keeps track of what would be on the
+ * screen, so that tests can feed it the output of a `TermWriter` and look at
+ * the result.
+ */
+pub struct Vt {
+    cols: uint,
+    rows: uint,
+    grid: Vec<Cell>,
+    // the normal screen, while the alternate one is shown
+    saved_grid: Option<Vec<Cell>>,
+    col: uint,
+    row: uint,
+    // set after writing to the last column, so that the next character
+    // wraps first, like xterm does
+    wrap_next: bool,
+    saved_cursor: (uint, uint),
+    attrs: Attrs,
+    // the first and last rows of the scroll region
+    top: uint,
+    bottom: uint,
+    cursor_visible: bool,
+    autowrap: bool,
+    // insert mode, where printing pushes the rest of the line right
+    insert: bool,
+    onlcr: bool,
+    state: State,
+    params: Vec<uint>,
+    // the colon separated values that followed each parameter, as in the
+    // `4:3` of `CSI 4:3 m`
+    subparams: Vec<Vec<uint>>,
+    // the ones for the parameter being read, once it has had a colon
+    sub: Option<Vec<uint>>,
+    param: Option<uint>,
+    private: bool,
+    // what has been read of the current osc sequence
+    osc: String,
+}
+
+enum State {
+    Ground,
+    Escape,
+    // an escape followed by something like `(`, which takes one more
+    // character to finish
+    EscapeCharset,
+    Csi,
+    Osc,
+}
+
+impl Vt {
+    /// Creates a blank terminal with `cols` columns and `rows` rows.
+    pub fn new (cols: uint, rows: uint) -> Vt {
+        Vt {
+            cols: cols,
+            rows: rows,
+            grid: Vec::from_elem(cols * rows, Cell::blank()),
+            saved_grid: None,
+            col: 0,
+            row: 0,
+            wrap_next: false,
+            saved_cursor: (0, 0),
+            attrs: Attrs::new(),
+            top: 0,
+            bottom: rows - 1,
+            cursor_visible: true,
+            autowrap: true,
+            insert: false,
+            onlcr: true,
+            state: Ground,
+            params: vec!(),
+            subparams: vec!(),
+            sub: None,
+            param: None,
+            private: false,
+            osc: String::new(),
+        }
+    }
+
+    /// The size of the terminal, as `(columns, rows)`.
+    pub fn size (&self) -> (uint, uint) {
+        (self.cols, self.rows)
+    }
+
+    /// The cell at (`col`, `row`).
+    pub fn cell<'a> (&'a self, col: uint, row: uint) -> &'a Cell {
+        assert!(col < self.cols && row < self.rows);
+        self.grid.get(row * self.cols + col)
+    }
+
+    /// The characters in `row`, without trailing spaces.
+    pub fn row_text (&self, row: uint) -> String {
+        let mut text = String::new();
+        for col in iter::range(0, self.cols) {
+            text.push_char(self.cell(col, row).ch);
+        }
+        text.as_slice().trim_right().to_string()
+    }
+
+    /// The position of the cursor, as `(col, row)`.
+    pub fn cursor (&self) -> (uint, uint) {
+        (self.col, self.row)
+    }
+
+    /// Whether the cursor is visible.
+    pub fn cursor_visible (&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// The attributes that text is currently being drawn with.
+    pub fn attrs<'a> (&'a self) -> &'a Attrs {
+        &self.attrs
+    }
+
+    /// The first and last rows of the scroll region.
+    pub fn scroll_region (&self) -> (uint, uint) {
+        (self.top, self.bottom)
+    }
+
+    /// Whether the alternate screen is being shown.
+    pub fn alternate_screen (&self) -> bool {
+        self.saved_grid.is_some()
+    }
+
+    /**
+     * Whether a newline also returns the cursor to the first column.
+     *
+     * This is on by default, since that is what the tty does to the output
+     * of a `TermWriter` before a real terminal sees it.
+     */
+    pub fn set_onlcr (&mut self, enabled: bool) {
+        self.onlcr = enabled;
+    }
+
+    /// Processes output sent to the terminal.
+    pub fn feed (&mut self, text: &str) {
+        for c in text.chars() {
+            self.feed_char(c);
+        }
+    }
+
+    fn feed_char (&mut self, c: char) {
+        match self.state {
+            Ground         => {
+                if c < ' ' || c == '\x7f' {
+                    self.control(c);
+                }
+                else {
+                    self.print(c);
+                }
+            }
+            Escape         => {
+                self.state = Ground;
+                self.escape(c);
+            }
+            EscapeCharset  => self.state = Ground,
+            Csi            => self.csi_char(c),
+            Osc            => self.osc_char(c),
+        }
+    }
+
+    fn osc_char (&mut self, c: char) {
+        match c {
+            // ST is escape followed by a backslash, which gets dropped as an
+            // unknown escape
+            '\x07' => self.state = Ground,
+            '\x1b' => self.state = Escape,
+            _      => {
+                self.osc.push_char(c);
+                // linux resets (`R`) and sets (`P` and 7 hex digits) its
+                // palette without any terminator
+                let osc = self.osc.as_slice();
+                if osc == "R" || (osc.starts_with("P") && osc.len() == 8) {
+                    self.state = Ground;
+                }
+            }
+        }
+    }
+
+    fn control (&mut self, c: char) {
+        let (col, row) = (self.col, self.row);
+        match c {
+            '\x1b'          => self.state = Escape,
+            '\r'            => self.move_to(0, row),
+            '\n'            => {
+                if self.onlcr {
+                    self.move_to(0, row);
+                }
+                self.line_feed();
+            }
+            // the tty only adds a carriage return to real newlines
+            '\x0b' | '\x0c' => self.line_feed(),
+            '\x08'          => {
+                let col = if col > 0 { col - 1 } else { 0 };
+                self.move_to(col, row);
+            }
+            '\t'            => {
+                let col = cmp::min((col / 8 + 1) * 8, self.cols - 1);
+                self.move_to(col, row);
+            }
+            // the bell, charset shifts and so on don't change the screen
+            _               => (),
+        }
+    }
+
+    fn escape (&mut self, c: char) {
+        match c {
+            '['             => {
+                self.state = Csi;
+                self.params.clear();
+                self.subparams.clear();
+                self.sub = None;
+                self.param = None;
+                self.private = false;
+            }
+            ']'             => {
+                self.state = Osc;
+                self.osc.clear();
+            }
+            '(' | ')' | '*' | '+' | '#' => self.state = EscapeCharset,
+            'D'             => self.line_feed(),
+            'E'             => {
+                let row = self.row;
+                self.move_to(0, row);
+                self.line_feed();
+            }
+            'M'             => self.reverse_line_feed(),
+            '7'             => self.saved_cursor = (self.col, self.row),
+            '8'             => {
+                let (col, row) = self.saved_cursor;
+                self.move_to(col, row);
+            }
+            'c'             => {
+                let onlcr = self.onlcr;
+                *self = Vt::new(self.cols, self.rows);
+                self.onlcr = onlcr;
+            }
+            // keypad modes and such
+            _               => (),
+        }
+    }
+
+    fn csi_char (&mut self, c: char) {
+        match c {
+            '0'..'9'          => {
+                let digit = c as uint - '0' as uint;
+                self.param = Some(self.param.unwrap_or(0) * 10 + digit);
+            }
+            ';'               => self.end_param(false),
+            ':'               => {
+                let p = self.param.take().unwrap_or(0);
+                if self.sub.is_none() {
+                    self.params.push(p);
+                    self.sub = Some(vec!());
+                }
+                else {
+                    self.sub.get_mut_ref().push(p);
+                }
+            }
+            '?' | '>' | '=' | '<' => self.private = true,
+            '\x1b'            => self.state = Escape,
+            '\x00'..'\x1f'    => self.control(c),
+            '\x40'..'\x7e'    => {
+                self.end_param(true);
+                self.state = Ground;
+                if self.private {
+                    self.private_mode(c);
+                }
+                else {
+                    self.csi(c);
+                }
+            }
+            // intermediate bytes, which nothing we understand uses
+            _                 => (),
+        }
+    }
+
+    // finishes the parameter that was being read. the last one only counts if
+    // it had anything in it, so that `CSI m` has no parameters at all.
+    fn end_param (&mut self, last: bool) {
+        let p = self.param.take();
+        match self.sub.take() {
+            Some(mut sub) => {
+                sub.push(p.unwrap_or(0));
+                self.subparams.push(sub);
+            }
+            None if last && p.is_none() => (),
+            None          => {
+                self.params.push(p.unwrap_or(0));
+                self.subparams.push(vec!());
+            }
+        }
+    }
+
+    // the `i`th parameter, where 0 or a missing one means `default`
+    fn param (&self, i: uint, default: uint) -> uint {
+        match self.params.as_slice().get(i) {
+            Some(&p) if p > 0 => p,
+            _                 => default,
+        }
+    }
+
+    fn csi (&mut self, c: char) {
+        let (col, row) = (self.col, self.row);
+        let (cols, rows) = (self.cols, self.rows);
+        let n = self.param(0, 1);
+        match c {
+            'A'       => {
+                let top = if row >= self.top { self.top } else { 0 };
+                let row = cmp::max(row as int - n as int, top as int);
+                self.move_to(col, row as uint);
+            }
+            'B'       => {
+                let bottom = if row <= self.bottom {
+                    self.bottom
+                }
+                else {
+                    rows - 1
+                };
+                self.move_to(col, cmp::min(row + n, bottom));
+            }
+            'C' | 'a' => {
+                self.move_to(cmp::min(col + n, cols - 1), row);
+            }
+            'D'       => {
+                let col = if col > n { col - n } else { 0 };
+                self.move_to(col, row);
+            }
+            'E'       => {
+                self.move_to(0, cmp::min(row + n, rows - 1));
+            }
+            'F'       => {
+                self.move_to(0, if row > n { row - n } else { 0 });
+            }
+            'G' | '`' => {
+                self.move_to(cmp::min(n - 1, cols - 1), row);
+            }
+            'H' | 'f' => {
+                let row = cmp::min(self.param(0, 1) - 1, rows - 1);
+                let col = cmp::min(self.param(1, 1) - 1, cols - 1);
+                self.move_to(col, row);
+            }
+            'd'       => {
+                self.move_to(col, cmp::min(n - 1, rows - 1));
+            }
+            'e'       => {
+                self.move_to(col, cmp::min(row + n, rows - 1));
+            }
+            'J'       => {
+                let start = self.row * self.cols + self.col;
+                let end = self.rows * self.cols;
+                match self.param(0, 0) {
+                    0 => self.erase(start, end),
+                    1 => self.erase(0, start + 1),
+                    _ => self.erase(0, end),
+                }
+            }
+            'K'       => {
+                let line = self.row * self.cols;
+                let here = line + self.col;
+                match self.param(0, 0) {
+                    0 => self.erase(here, line + self.cols),
+                    1 => self.erase(line, here + 1),
+                    _ => self.erase(line, line + self.cols),
+                }
+            }
+            'X'       => {
+                let here = self.row * self.cols + self.col;
+                let end = self.row * self.cols + cmp::min(self.col + n, self.cols);
+                self.erase(here, end);
+            }
+            '@'       => self.insert_chars(n),
+            'h' | 'l' => {
+                if self.params.contains(&4) {
+                    self.insert = c == 'h';
+                }
+            }
+            'P'       => self.delete_chars(n),
+            'L'       => {
+                if self.row >= self.top && self.row <= self.bottom {
+                    let (row, bottom) = (self.row, self.bottom);
+                    self.scroll_down(row, bottom, n);
+                    self.move_to(0, row);
+                }
+            }
+            'M'       => {
+                if self.row >= self.top && self.row <= self.bottom {
+                    let (row, bottom) = (self.row, self.bottom);
+                    self.scroll_up(row, bottom, n);
+                    self.move_to(0, row);
+                }
+            }
+            'S'       => {
+                let (top, bottom) = (self.top, self.bottom);
+                self.scroll_up(top, bottom, n);
+            }
+            'T'       => {
+                let (top, bottom) = (self.top, self.bottom);
+                self.scroll_down(top, bottom, n);
+            }
+            'r'       => {
+                let top = self.param(0, 1) - 1;
+                let bottom = cmp::min(self.param(1, self.rows), self.rows) - 1;
+                if top < bottom {
+                    self.top = top;
+                    self.bottom = bottom;
+                    self.move_to(0, 0);
+                }
+            }
+            'm'       => self.sgr(),
+            's'       => self.saved_cursor = (self.col, self.row),
+            'u'       => {
+                let (col, row) = self.saved_cursor;
+                self.move_to(col, row);
+            }
+            // window manipulation, device attributes and such
+            _         => (),
+        }
+    }
+
+    fn private_mode (&mut self, c: char) {
+        let enable = match c {
+            'h' => true,
+            'l' => false,
+            _   => return,
+        };
+        let params = self.params.clone();
+        for &mode in params.iter() {
+            match mode {
+                7                => self.autowrap = enable,
+                25               => self.cursor_visible = enable,
+                47 | 1047 | 1049 => {
+                    if mode == 1049 && enable {
+                        self.saved_cursor = (self.col, self.row);
+                    }
+                    self.switch_screen(enable);
+                    if mode == 1049 && !enable {
+                        let (col, row) = self.saved_cursor;
+                        self.move_to(col, row);
+                    }
+                }
+                // mouse reporting, bracketed paste and such
+                _                => (),
+            }
+        }
+    }
+
+    fn switch_screen (&mut self, alternate: bool) {
+        if alternate == self.saved_grid.is_some() {
+            return;
+        }
+        if alternate {
+            let blank = Vec::from_elem(self.cols * self.rows, Cell::blank());
+            let main = mem::replace(&mut self.grid, blank);
+            self.saved_grid = Some(main);
+        }
+        else {
+            self.grid = self.saved_grid.take().unwrap();
+        }
+    }
+
+    fn sgr (&mut self) {
+        if self.params.len() == 0 {
+            self.attrs = Attrs::new();
+            return;
+        }
+
+        let params = self.params.clone();
+        let subparams = self.subparams.clone();
+        let mut i = 0;
+        while i < params.len() {
+            let p = *params.get(i);
+            let sub = subparams.get(i).as_slice();
+            match p {
+                0         => self.attrs = Attrs::new(),
+                1         => self.attrs.bold = true,
+                2         => self.attrs.dim = true,
+                3         => self.attrs.italic = true,
+                4 if sub.len() > 0 => {
+                    self.attrs.underline = sub[0] != 0;
+                    match underline_style(sub[0]) {
+                        Some(style) => self.attrs.underline_style = style,
+                        None        => (),
+                    }
+                }
+                4         => {
+                    self.attrs.underline = true;
+                    self.attrs.underline_style = SingleUnderline;
+                }
+                5         => self.attrs.blink = true,
+                7         => self.attrs.reverse = true,
+                8         => self.attrs.invisible = true,
+                9         => self.attrs.strikethrough = true,
+                21        => {
+                    self.attrs.underline = true;
+                    self.attrs.underline_style = DoubleUnderline;
+                }
+                22        => {
+                    self.attrs.bold = false;
+                    self.attrs.dim = false;
+                }
+                23        => self.attrs.italic = false,
+                24        => self.attrs.underline = false,
+                25        => self.attrs.blink = false,
+                27        => self.attrs.reverse = false,
+                28        => self.attrs.invisible = false,
+                29        => self.attrs.strikethrough = false,
+                53        => self.attrs.overline = true,
+                55        => self.attrs.overline = false,
+                30..37    => self.attrs.fg = AnsiColor(color::ansi(p - 30)),
+                39        => self.attrs.fg = DefaultColor,
+                40..47    => self.attrs.bg = AnsiColor(color::ansi(p - 40)),
+                49        => self.attrs.bg = DefaultColor,
+                90..97    => self.attrs.fg = BrightColor(color::ansi(p - 90)),
+                100..107  => self.attrs.bg = BrightColor(color::ansi(p - 100)),
+                // extended colors, which are either given in subparameters,
+                // like `38:2::r:g:b`, or take up the next few parameters
+                38 | 48 | 58 => {
+                    let (c, n) = if sub.len() == 0 {
+                        extended_color(params.slice_from(i + 1))
+                    }
+                    else {
+                        // rgb values can have a colorspace id before them,
+                        // which is just skipped
+                        let (c, _) = match sub {
+                            [2, _, r, g, b] => extended_color([2, r, g, b]),
+                            _               => extended_color(sub),
+                        };
+                        (c, 0)
+                    };
+                    match (p, c) {
+                        (38, Some(c)) => self.attrs.fg = c,
+                        (48, Some(c)) => self.attrs.bg = c,
+                        (_, Some(c))  => self.attrs.underline_color = c,
+                        (_, None)     => (),
+                    }
+                    i += n;
+                }
+                59        => self.attrs.underline_color = DefaultColor,
+                _         => (),
+            }
+            i += 1;
+        }
+    }
+
+    fn print (&mut self, c: char) {
+        if self.wrap_next {
+            let row = self.row;
+            self.move_to(0, row);
+            self.line_feed();
+        }
+        if self.insert {
+            self.insert_chars(1);
+        }
+        let i = self.row * self.cols + self.col;
+        let cell = Cell { ch: c, attrs: self.attrs.clone() };
+        *self.grid.get_mut(i) = cell;
+        if self.col + 1 < self.cols {
+            self.col += 1;
+        }
+        else if self.autowrap {
+            self.wrap_next = true;
+        }
+    }
+
+    fn move_to (&mut self, col: uint, row: uint) {
+        self.col = col;
+        self.row = row;
+        self.wrap_next = false;
+    }
+
+    fn line_feed (&mut self) {
+        if self.row == self.bottom {
+            let (top, bottom) = (self.top, self.bottom);
+            self.scroll_up(top, bottom, 1);
+        }
+        else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+        self.wrap_next = false;
+    }
+
+    fn reverse_line_feed (&mut self) {
+        if self.row == self.top {
+            let (top, bottom) = (self.top, self.bottom);
+            self.scroll_down(top, bottom, 1);
+        }
+        else if self.row > 0 {
+            self.row -= 1;
+        }
+        self.wrap_next = false;
+    }
+
+    // erasing fills with the current background color, like xterm does
+    fn blank (&self) -> Cell {
+        let mut cell = Cell::blank();
+        cell.attrs.bg = self.attrs.bg;
+        cell
+    }
+
+    fn erase (&mut self, from: uint, to: uint) {
+        let blank = self.blank();
+        for i in iter::range(from, to) {
+            *self.grid.get_mut(i) = blank.clone();
+        }
+    }
+
+    // moves rows [top + n, bottom] up to top, and blanks the rest
+    fn scroll_up (&mut self, top: uint, bottom: uint, n: uint) {
+        let n = cmp::min(n, bottom - top + 1);
+        for row in iter::range(top, bottom + 1 - n) {
+            self.copy_row(row + n, row);
+        }
+        let cols = self.cols;
+        self.erase((bottom + 1 - n) * cols, (bottom + 1) * cols);
+    }
+
+    // moves rows [top, bottom - n] down to top + n, and blanks the rest
+    fn scroll_down (&mut self, top: uint, bottom: uint, n: uint) {
+        let n = cmp::min(n, bottom - top + 1);
+        for row in iter::range(top + n, bottom + 1).rev() {
+            self.copy_row(row - n, row);
+        }
+        let cols = self.cols;
+        self.erase(top * cols, (top + n) * cols);
+    }
+
+    fn copy_row (&mut self, from: uint, to: uint) {
+        for col in iter::range(0, self.cols) {
+            let cell = self.grid.get(from * self.cols + col).clone();
+            *self.grid.get_mut(to * self.cols + col) = cell;
+        }
+    }
+
+    fn insert_chars (&mut self, n: uint) {
+        let line = self.row * self.cols;
+        let n = cmp::min(n, self.cols - self.col);
+        for col in iter::range(self.col + n, self.cols).rev() {
+            let cell = self.grid.get(line + col - n).clone();
+            *self.grid.get_mut(line + col) = cell;
+        }
+        let here = line + self.col;
+        self.erase(here, here + n);
+    }
+
+    fn delete_chars (&mut self, n: uint) {
+        let line = self.row * self.cols;
+        let n = cmp::min(n, self.cols - self.col);
+        for col in iter::range(self.col, self.cols - n) {
+            let cell = self.grid.get(line + col + n).clone();
+            *self.grid.get_mut(line + col) = cell;
+        }
+        self.erase(line + self.cols - n, line + self.cols);
+    }
+}
+
+// the color at the start of `params`, after a 38, 48 or 58, and how many
+// parameters it takes up
+fn extended_color (params: &[uint]) -> (Option<Color>, uint) {
+    match params {
+        [5, n, ..] if n < 256 => (Some(color::from_index(n as u8)), 2),
+        [2, r, g, b, ..] if r < 256 && g < 256 && b < 256 => {
+            (Some(RgbColor(r as u8, g as u8, b as u8)), 4)
+        }
+        [5, ..]               => (None, 2),
+        [2, ..]               => (None, 4),
+        _                     => (None, 0),
+    }
+}
+
+fn underline_style (n: uint) -> Option<UnderlineStyle> {
+    match n {
+        1 => Some(SingleUnderline),
+        2 => Some(DoubleUnderline),
+        3 => Some(CurlyUnderline),
+        4 => Some(DottedUnderline),
+        5 => Some(DashedUnderline),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+fn rows (vt: &Vt) -> Vec<String> {
+    let (_, n) = vt.size();
+    iter::range(0, n).map(|row| { vt.row_text(row) }).collect()
+}
+
+#[test]
+fn test_terminfo_escapes () {
+    use fixtures::{LinuxEntry, XtermEntry};
+
+    for entry in [XtermEntry.as_slice(), LinuxEntry.as_slice()].iter() {
+        let info = info::TermInfo::from_bytes(*entry).unwrap();
+        let esc = |cap: &str| { info.escape(cap).unwrap().unwrap() };
+        let esc2 = |cap: &str, p1: int, p2: int| {
+            info.escape2(cap, p1, p2).unwrap().unwrap()
+        };
+
+        let mut vt = Vt::new(20, 10);
+        vt.feed("garbage");
+        vt.feed(esc("clear").as_slice());
+        assert!(vt.row_text(0).as_slice() == "");
+        assert!(vt.cursor() == (0, 0));
+
+        vt.feed(esc2("cup", 5, 3).as_slice());
+        vt.feed(info.escape1("setaf", 1).unwrap().unwrap().as_slice());
+        vt.feed(esc("bold").as_slice());
+        vt.feed("@");
+        vt.feed(esc("sgr0").as_slice());
+        vt.feed("x");
+
+        let mut red_bold = Attrs::new();
+        red_bold.fg = AnsiColor(info::ColorRed);
+        red_bold.bold = true;
+        assert!(vt.cell(3, 5) == &Cell { ch: '@', attrs: red_bold });
+        assert!(vt.cell(4, 5) == &Cell { ch: 'x', attrs: Attrs::new() });
+        assert!(vt.cursor() == (5, 5));
+
+        vt.feed(esc("home").as_slice());
+        vt.feed(esc("ri").as_slice());
+        assert!(vt.row_text(6).as_slice() == "   @x");
+
+        vt.feed(esc("civis").as_slice());
+        assert!(!vt.cursor_visible());
+        vt.feed(esc("cnorm").as_slice());
+        assert!(vt.cursor_visible());
+    }
+
+    // linux doesn't end its palette escapes like other osc sequences
+    let linux = info::TermInfo::from_bytes(LinuxEntry.as_slice()).unwrap();
+    let initc = linux.escapen("initc", [1, 1000, 500, 0]).unwrap().unwrap();
+    let mut vt = Vt::new(20, 10);
+    vt.feed(linux.escape("oc").unwrap().unwrap().as_slice());
+    vt.feed(initc.as_slice());
+    vt.feed("ok");
+    assert!(vt.row_text(0).as_slice() == "ok");
+}
+
+#[test]
+fn test_alternate_screen () {
+    use fixtures::XtermEntry;
+
+    let xterm = info::TermInfo::from_bytes(XtermEntry.as_slice()).unwrap();
+    let mut vt = Vt::new(10, 3);
+    vt.feed("main");
+    vt.feed(xterm.escape("smcup").unwrap().unwrap().as_slice());
+    assert!(vt.alternate_screen());
+    assert!(vt.row_text(0).as_slice() == "");
+    vt.feed("\x1b[Halt");
+    vt.feed(xterm.escape("rmcup").unwrap().unwrap().as_slice());
+    assert!(!vt.alternate_screen());
+    assert!(vt.row_text(0).as_slice() == "main");
+    assert!(vt.cursor() == (4, 0));
+}
+
+#[test]
+fn test_wrap_and_scroll () {
+    let mut vt = Vt::new(3, 2);
+    vt.feed("abcdef");
+    assert!(rows(&vt) == vec!("abc".to_string(), "def".to_string()));
+    assert!(vt.cursor() == (2, 1));
+    vt.feed("g");
+    assert!(rows(&vt) == vec!("def".to_string(), "g".to_string()));
+
+    // vertical tabs and form feeds keep the column, unlike newlines
+    let mut vt = Vt::new(5, 3);
+    vt.feed("ab\x0bc\x0cd");
+    assert!(rows(&vt) == vec!(
+        "ab".to_string(), "  c".to_string(), "   d".to_string()
+    ));
+
+    let mut vt = Vt::new(5, 4);
+    vt.feed("1\n2\n3\n4");
+    vt.feed("\x1b[2;3r");
+    assert!(vt.scroll_region() == (1, 2));
+    vt.feed("\x1b[3;1H\n");
+    assert!(rows(&vt) == vec!(
+        "1".to_string(), "3".to_string(), "".to_string(), "4".to_string()
+    ));
+    vt.feed("\x1b[2;1H\x1bM");
+    assert!(rows(&vt) == vec!(
+        "1".to_string(), "".to_string(), "3".to_string(), "4".to_string()
+    ));
+}
+
+#[test]
+fn test_editing () {
+    let mut vt = Vt::new(8, 3);
+    vt.feed("abcdefgh\x1b[2;1H12345678\x1b[3;1Hxyz");
+
+    vt.feed("\x1b[1;3H\x1b[2P");
+    assert!(vt.row_text(0).as_slice() == "abefgh");
+    vt.feed("\x1b[2@");
+    assert!(vt.row_text(0).as_slice() == "ab  efgh");
+    vt.feed("\x1b[1;2H\x1b[3X");
+    assert!(vt.row_text(0).as_slice() == "a   efgh");
+    vt.feed("\x1b[1;6H\x1b[K");
+    assert!(vt.row_text(0).as_slice() == "a   e");
+
+    vt.feed("\x1b[2;1H\x1b[L");
+    assert!(rows(&vt) == vec!(
+        "a   e".to_string(), "".to_string(), "12345678".to_string()
+    ));
+    vt.feed("\x1b[M\x1b[M");
+    assert!(rows(&vt) == vec!("a   e".to_string(), "".to_string(), "".to_string()));
+
+    vt.feed("\x1b[41m\x1b[2J");
+    assert!(vt.cell(0, 0).attrs.bg == AnsiColor(info::ColorRed));
+}
+
+#[test]
+fn test_extended_colors () {
+    use color::IndexedColor;
+
+    let mut vt = Vt::new(8, 1);
+    vt.feed("\x1b[38;5;200;48;2;1;2;3ma\x1b[91;38;5;4mb\x1b[39;49mc");
+    assert!(vt.cell(0, 0).attrs.fg == IndexedColor(200));
+    assert!(vt.cell(0, 0).attrs.bg == RgbColor(1, 2, 3));
+    assert!(vt.cell(1, 0).attrs.fg == AnsiColor(info::ColorBlue));
+    assert!(vt.cell(1, 0).attrs.bg == RgbColor(1, 2, 3));
+    assert!(vt.cell(2, 0).attrs == Attrs::new());
+}
+
+#[test]
+fn test_underline_styles () {
+    let mut vt = Vt::new(8, 1);
+    vt.feed("\x1b[4:3;58:2::1:2:3ma\x1b[4:0mb\x1b[21;58;5;9mc\x1b[59;24md");
+    let a = vt.cell(0, 0).attrs.clone();
+    assert!(a.underline && a.underline_style == CurlyUnderline);
+    assert!(a.underline_color == RgbColor(1, 2, 3));
+    assert!(!vt.cell(1, 0).attrs.underline);
+    let c = vt.cell(2, 0).attrs.clone();
+    assert!(c.underline && c.underline_style == DoubleUnderline);
+    assert!(c.underline_color == BrightColor(info::ColorRed));
+    let d = vt.cell(3, 0).attrs.clone();
+    assert!(!d.underline && d.underline_color == DefaultColor);
+}
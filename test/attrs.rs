@@ -1,9 +1,19 @@
 extern crate termutils;
 use std::io;
 
+fn show (escape: termutils::info::InfoResult<String>) {
+    match escape {
+        Ok(e)  => println!("{}", e),
+        Err(e) => println!("{}", e),
+    }
+}
+
 fn main () {
-    termutils::info::init();
-    println!("{}", termutils::info::exit_attribute_mode());
+    match termutils::info::init() {
+        Ok(()) => (),
+        Err(e) => fail!("{}", e),
+    }
+    show(termutils::info::exit_attribute_mode());
     let mut reader = io::stdin();
     loop {
         println!("Attribute?");
@@ -11,10 +21,10 @@ fn main () {
         attr = attr.replace("\n", "");
         if attr.as_slice().starts_with("fg:") || attr.as_slice().starts_with("bg:") {
             let set = if attr.as_slice().starts_with("fg:") {
-                |c| { println!("{}", termutils::info::set_a_foreground(c)) }
+                |c| { show(termutils::info::set_a_foreground(c)) }
             }
             else {
-                |c| { println!("{}", termutils::info::set_a_background(c)) }
+                |c| { show(termutils::info::set_a_background(c)) }
             };
 
             match attr.as_slice().slice_from(3) {
@@ -31,17 +41,17 @@ fn main () {
         }
         else {
             match attr.as_slice() {
-                "underline"   => println!("{}", termutils::info::enter_underline_mode()),
-                "standout"    => println!("{}", termutils::info::enter_standout_mode()),
-                "reverse"     => println!("{}", termutils::info::enter_reverse_mode()),
-                "bold"        => println!("{}", termutils::info::enter_bold_mode()),
-                "blink"       => println!("{}", termutils::info::enter_blink_mode()),
-                "reset"       => println!("{}", termutils::info::exit_attribute_mode()),
-                "reset_color" => println!("{}", termutils::info::orig_pair()),
+                "underline"   => show(termutils::info::enter_underline_mode()),
+                "standout"    => show(termutils::info::enter_standout_mode()),
+                "reverse"     => show(termutils::info::enter_reverse_mode()),
+                "bold"        => show(termutils::info::enter_bold_mode()),
+                "blink"       => show(termutils::info::enter_blink_mode()),
+                "reset"       => show(termutils::info::exit_attribute_mode()),
+                "reset_color" => show(termutils::info::orig_pair()),
                 ""            => break,
                 _              => (),
             }
         }
     }
-    println!("{}", termutils::info::exit_attribute_mode());
+    show(termutils::info::exit_attribute_mode());
 }
@@ -1,17 +1,27 @@
 extern crate termutils;
 use termutils::hexes::Term;
-use termutils::hexes::{KeyCharacter};
+use termutils::hexes::{Key, KeyCharacter, KeyEvent, MouseDrags};
 
 fn main () {
-    termutils::ios::preserve(|| {
-        let mut term = Term::new();
+    let tty = termutils::ios::open_tty().unwrap();
+    termutils::ios::preserve(tty, || {
+        let mut term = match Term::new() {
+            Ok(term) => term,
+            Err(e)   => fail!("{}", e),
+        };
+        term.mouse(Some(MouseDrags));
+        term.bracketed_paste(true);
+        term.add_timer(5000, true);
         loop {
-            let k = match term.read() {
-                Some(KeyCharacter('q')) => break,
-                Some(key) => key,
-                None      => break,
+            let e = match term.read() {
+                Some(KeyEvent(Key { code: KeyCharacter('q'), .. })) => break,
+                Some(e) => e,
+                None    => break,
             };
-            println!("Got key: {}", k);
+            println!("Got event: {}", e);
         }
+        term.mouse(None);
+        term.bracketed_paste(false);
+        term.flush();
     });
 }
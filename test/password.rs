@@ -3,9 +3,10 @@ use std::io;
 
 fn main () {
     print!("Enter password: ");
-    termutils::ios::echo(false);
+    let tty = termutils::ios::open_tty().unwrap();
+    termutils::ios::echo(tty, false);
     let mut reader = io::stdin();
     let pass = reader.read_line().unwrap_or("nothing".to_string());
-    termutils::ios::echo(true);
+    termutils::ios::echo(tty, true);
     println!("\nYour password is: {:s}", pass);
 }
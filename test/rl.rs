@@ -1,8 +1,9 @@
 extern crate termutils;
 use std::iter;
 use termutils::hexes::Term;
-use termutils::hexes::{KeyCharacter,KeyEscape,KeyUp,KeyDown,KeyLeft,KeyRight,KeyF};
-use termutils::info::{Color,ColorRed};
+use termutils::hexes::{KeyEvent,KeyCharacter,KeyEscape,KeyUp,KeyDown,KeyLeft,KeyRight,KeyF};
+use termutils::color::{Color,AnsiColor};
+use termutils::info::ColorRed;
 
 fn draw_map (term: &mut Term, color: Option<Color>,
              rows: uint, cols: uint) {
@@ -38,11 +39,15 @@ fn draw_ground (term: &mut Term, color: Option<Color>,
 }
 
 fn main () {
-    let (cols, rows) = termutils::ios::size();
+    let tty = termutils::ios::open_tty().unwrap();
+    let (cols, rows) = termutils::ios::size(tty);
 
     {
         println!("Get a new term");
-        let mut term = Term::new();
+        let mut term = match Term::new() {
+            Ok(term) => term,
+            Err(e)   => fail!("{}", e),
+        };
         println!("got term");
 
         let mut x = 0u;
@@ -56,8 +61,9 @@ fn main () {
         loop {
             draw_character(&mut term, None, x, y);
             let k = match term.read() {
-                Some(key) => key,
-                None      => break,
+                Some(KeyEvent(key)) => key.code,
+                Some(_)             => continue,
+                None                => break,
             };
             draw_ground(&mut term, color, x, y);
 
@@ -70,7 +76,7 @@ fn main () {
                 KeyCharacter('l') | KeyRight if x < cols - 1 => { x += 1 }
 
                 KeyF(1) => {
-                    color = Some(ColorRed);
+                    color = Some(AnsiColor(ColorRed));
                     draw_map(&mut term, color, rows, cols);
                 }
                 KeyF(6) => {
@@ -78,7 +84,7 @@ fn main () {
                     draw_map(&mut term, color, rows, cols);
                 }
 
-                KeyCharacter(' ') => { term.cursor(cursor); cursor = !cursor }
+                KeyCharacter(' ') => { cursor = !cursor; term.cursor(cursor) }
 
                 _   => { }
             }
@@ -4,26 +4,56 @@ use termutils::ios;
 use termutils::hexes::*;
 
 fn main () {
-    termutils::ios::preserve(|| {
-        let (_, rows) = termutils::ios::size();
-        let mut term = Term::new();
+    let tty = termutils::ios::open_tty().unwrap();
+    termutils::ios::preserve(tty, || {
+        let (_, rows) = termutils::ios::size(tty);
+        let mut term = match Term::new() {
+            Ok(term) => term,
+            Err(e)   => fail!("{}", e),
+        };
 
         for i in range(1, rows) {
             term.write(format!("{}\n", i).as_slice());
         }
         term.write(format!("{}", rows).as_slice());
 
+        // 'r' limits scrolling to the middle half of the screen
+        let (top, bottom) = (rows / 4, rows * 3 / 4);
+        let mut region = false;
+
         loop {
-            match term.read() {
-                Some(KeyCharacter('q')) => break,
-                Some(KeyUp) => {
-                    term.move_cursor(0, 0);
+            let k = match term.read() {
+                Some(KeyEvent(key)) => key.code,
+                Some(_)             => continue,
+                None                => break,
+            };
+            match k {
+                KeyCharacter('q') => break,
+                KeyUp => {
+                    term.move_cursor(0, if region { top } else { 0 });
                     term.scroll_reverse(2);
                 }
-                Some(KeyDown) => {
-                    term.move_cursor(0, rows - 1);
+                KeyDown => {
+                    term.move_cursor(0, if region { bottom } else { rows - 1 });
                     term.scroll_forward(2);
                 }
+                KeyCharacter('r') => {
+                    region = !region;
+                    if region {
+                        term.set_scroll_region(top, bottom);
+                    }
+                    else {
+                        term.reset_scroll_region();
+                    }
+                }
+                KeyCharacter('i') => {
+                    term.move_cursor(0, top);
+                    term.insert_lines(1);
+                }
+                KeyCharacter('d') => {
+                    term.move_cursor(0, top);
+                    term.delete_lines(1);
+                }
                 _ => (),
             };
         }
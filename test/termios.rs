@@ -2,9 +2,10 @@ extern crate termutils;
 use std::os;
 
 fn main () {
+    let tty = termutils::ios::open_tty().unwrap();
     match os::args()[1].as_slice() {
-        "echo"   => termutils::ios::echo(true),
-        "noecho" => termutils::ios::echo(false),
+        "echo"   => termutils::ios::echo(tty, true),
+        "noecho" => termutils::ios::echo(tty, false),
         _         => fail!("unknown argument"),
     };
 }
@@ -15,8 +15,9 @@ fn loop_chars () {
 
 fn main () {
     println!("In raw mode (q to exit):");
-    termutils::ios::preserve(|| {
-        termutils::ios::raw();
+    let tty = termutils::ios::open_tty().unwrap();
+    termutils::ios::preserve(tty, || {
+        termutils::ios::raw(tty);
         loop_chars();
     });
 
@@ -1,6 +1,7 @@
 extern crate termutils;
 
 fn main () {
-    let (cols, rows) = termutils::ios::size();
+    let tty = termutils::ios::open_tty().unwrap();
+    let (cols, rows) = termutils::ios::size(tty);
     println!("tty: {:d} {:d}", cols as int, rows as int);
 }
@@ -6,10 +6,10 @@ fn main () {
         fail!("usage: tput <terminfo capability>");
     }
 
-    termutils::info::init();
     let ref attr = os::args()[1];
-    let escape = termutils::info::escape(attr.as_slice()).expect(
-        format!("{:s} is not supported on this terminal", *attr).as_slice()
-    );
-    println!("{}", escape);
+    match termutils::info::escape(attr.as_slice()) {
+        Ok(Some(escape)) => println!("{}", escape),
+        Ok(None)         => fail!("{:s} is not supported on this terminal", *attr),
+        Err(e)           => fail!("{}", e),
+    }
 }
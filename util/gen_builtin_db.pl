@@ -67,6 +67,22 @@ my @caps = qw(
     kf12
 );
 
+my @numbers = qw(
+    cols
+    lines
+    it
+    colors
+    pairs
+);
+
+my @flags = qw(
+    am
+    xenl
+    bce
+    msgr
+    km
+);
+
 my @db;
 for my $term (@terms) {
     my @vals;
@@ -101,3 +117,33 @@ for my $row (@db) {
     print "    ],\n";
     $i++;
 }
+
+print "];\n\n";
+print "// NOTE: generated by util/gen_builtin_db.pl\n";
+print "static number_db: [[Option<int>, ..NumNumbers], ..NumTerms] = [\n";
+for my $term (@terms) {
+    print "    [ // $term\n";
+    for my $cap (@numbers) {
+        my $val = `tput -T$term $cap`;
+        chomp $val;
+        if ($? == 0 && $val >= 0) {
+            print "        Some($val), // $cap\n";
+        }
+        else {
+            print "        None, // $cap\n";
+        }
+    }
+    print "    ],\n";
+}
+
+print "];\n\n";
+print "// NOTE: generated by util/gen_builtin_db.pl\n";
+print "static flag_db: [[bool, ..NumFlags], ..NumTerms] = [\n";
+for my $term (@terms) {
+    print "    [ // $term\n";
+    for my $cap (@flags) {
+        system("tput -T$term $cap");
+        print "        " . ($? == 0 ? "true" : "false") . ", // $cap\n";
+    }
+    print "    ],\n";
+}